@@ -1,9 +1,9 @@
 use std::fs;
 
 use interactive::start_interactive;
-use l_robot::{parser::parsers::parse, resolve_lines, tokenizer::tokenize};
+use l_robot::{parser::parsers::parse, resolve_lines_with, tokenizer::tokenize};
 
-use l_robot::resolver::resolve_message::{ResolveMessage, ResolveMessageType};
+use l_robot::resolver::{DEFAULT_DECIMAL_PRECISION, NumberMode, RationalFormat, Resolver, Tolerance, resolve_message::{ResolveMessage, ResolveMessageType}};
 
 use clap::{Arg, App};
 use colored::Colorize;
@@ -18,6 +18,35 @@ fn main() {
         .arg(Arg::new("file")
             .takes_value(true)
             .short('f'))
+        .arg(Arg::new("physics")
+            .long("physics")
+            .short('p')
+            .help("Defines physical constants (G, c, h, k_B, N_A)"))
+        .arg(Arg::new("exact")
+            .long("exact")
+            .short('e')
+            .conflicts_with("decimal")
+            .help("Calculates with exact fractions, ex. 1/3 + 1/6 = 1/2"))
+        .arg(Arg::new("decimal")
+            .long("decimal")
+            .short('d')
+            .help("Calculates with decimals which do not lose digits, ex. for sums of money, without units"))
+        .arg(Arg::new("precision")
+            .long("precision")
+            .takes_value(true)
+            .help("Significant digits kept by divisions with --decimal, 28 by default"))
+        .arg(Arg::new("recursion-limit")
+            .long("recursion-limit")
+            .takes_value(true)
+            .help("Nested calls of user functions allowed, 200 by default and at most 500"))
+        .arg(Arg::new("tolerance")
+            .long("tolerance")
+            .takes_value(true)
+            .help("Relative difference of values which are still equal, 1e-9 by default"))
+        .arg(Arg::new("trace")
+            .long("trace")
+            .short('t')
+            .help("Explains the steps of solving equations"))
         .arg(Arg::new("INPUT")
             .index(1))
         .subcommand(App::new("latex")
@@ -54,22 +83,47 @@ fn main() {
         Some(("interactive", _)) => start_interactive(),
         // no subcommands or unknown
         _ => {
+            let mut resolver = if matches.is_present("physics") {
+                Resolver::new().with_physics_constants()
+            } else {
+                Resolver::new()
+            };
+            if matches.is_present("trace") {
+                resolver = resolver.with_trace();
+            }
+            if let Some(limit) = matches.value_of("recursion-limit") {
+                resolver = resolver.with_recursion_limit(limit.parse().expect("Recursion limit must be a positive integer"));
+            }
+            if let Some(relative) = matches.value_of("tolerance") {
+                let relative = relative.parse().expect("Tolerance must be a number");
+                resolver = resolver.with_tolerance(Tolerance { relative, ..Tolerance::default() });
+            }
+            if matches.is_present("exact") {
+                resolver = resolver.with_number_mode(NumberMode::Rational(RationalFormat::Fraction));
+            }
+            if matches.is_present("decimal") {
+                let precision = match matches.value_of("precision") {
+                    Some(precision) => precision.parse().expect("Precision must be a positive integer"),
+                    None => DEFAULT_DECIMAL_PRECISION,
+                };
+                resolver = resolver.with_number_mode(NumberMode::Decimal { precision });
+            }
             let output = if let Some(filename) = matches.value_of("file") {
                 if let Ok(content) = fs::read_to_string(filename) {
-                    resolve_lines(content.lines().map(|x| x.to_string()).collect())
+                    resolve_lines_with(&mut resolver, content.lines().map(|x| x.to_string()).collect())
                 } else {
                     panic!("Could not load file");
                 }
             } else {
                 let str = matches.value_of("INPUT").unwrap();
-                resolve_lines(str.split(';').map(|x| x.to_string()).collect())
+                resolve_lines_with(&mut resolver, str.split(';').map(|x| x.to_string()).collect())
                 // let tokens = tokenize(&str).unwrap();
                 // let tree = parse(&tokens).unwrap();
                 // let mut resolver = Resolver::new();
                 // let output = resolver.resolve(vec![(1, tree)]);
             };
 
-            for (line_num, ResolveMessage { msg_type, content: message}) in output {
+            for (line_num, ResolveMessage { msg_type, content: message, .. }) in output {
                 let message = match msg_type {
                     ResolveMessageType::Error => message.red(),
                     ResolveMessageType::Info => message.blue(),
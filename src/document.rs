@@ -0,0 +1,286 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::{parse_line, parser::{node::{ASTNode, ASTNodeType}, walkers::post_order}, resolver::{Resolver, namespace::NamespaceElement, resolve_message::ResolveMessage}, tokenizer::{NumberLiterals, Token}};
+
+/// Messages of re-resolved lines, keyed by line index
+pub type Updates = BTreeMap<usize, Vec<ResolveMessage>>;
+
+/// Lines of text which stay resolved as they are edited, for use as an editor backend.
+/// Every line records which names it reads and which it defines, so that after an edit only the lines
+/// depending on it are resolved again. Lines are indexed from 0.
+#[derive(Clone, Debug)]
+pub struct Document {
+    /// Resolver with the namespace before the first line
+    base: Resolver,
+    resolver: Resolver,
+    lines: Vec<Line>,
+    next_group: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Line {
+    text: String,
+    node: Result<ASTNode, ResolveMessage>,
+    /// Names and functions used on the line
+    reads: HashSet<String>,
+    /// Namespace entries set by resolving the line
+    defines: HashMap<String, NamespaceElement>,
+    /// Lines resolved in the same step share a group, ex. equations solved as a system
+    group: usize,
+    messages: Vec<ResolveMessage>,
+}
+
+impl Line {
+    fn new(text: String, group: usize, literals: NumberLiterals) -> Self {
+        let mut line = Line { text: String::new(), node: Ok(ASTNode::empty(vec![])), reads: HashSet::new(), defines: HashMap::new(), group, messages: Vec::new() };
+        line.set_text(text, literals);
+        line
+    }
+
+    /// Parses the new text, keeping what the line defined before so that changes can be detected
+    fn set_text(&mut self, text: String, literals: NumberLiterals) {
+        self.node = parse_line(&text, literals).map_err(|err| err.to_resolve_message());
+        let mut reads = HashSet::new();
+        if let Ok(node) = &self.node {
+            post_order(node, &mut |x| match &x.node_type {
+                ASTNodeType::Delimeter(Token::Name(name)) | ASTNodeType::Function(name) => { reads.insert(name.clone()); },
+                _ => (),
+            });
+        }
+        self.reads = reads;
+        self.text = text;
+    }
+}
+
+impl Document {
+    pub fn new(lines: Vec<String>) -> Self {
+        Self::with_resolver(Resolver::new(), lines)
+    }
+
+    /// Creates a document resolved with an existing resolver, ex. one with physics constants defined
+    pub fn with_resolver(resolver: Resolver, lines: Vec<String>) -> Self {
+        let mut document = Document { base: resolver.clone(), resolver, lines: Vec::new(), next_group: lines.len() };
+        let literals = document.base.number_mode.literals();
+        document.lines = lines.into_iter().enumerate().map(|(i, text)| Line::new(text, i, literals)).collect();
+        document.update(0, (0..document.lines.len()).collect(), HashSet::new());
+        document
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub fn line(&self, index: usize) -> &str {
+        &self.lines[index].text
+    }
+
+    pub fn messages(&self, index: usize) -> &[ResolveMessage] {
+        &self.lines[index].messages
+    }
+
+    /// Messages of every line, in the same form as `resolve_lines`
+    pub fn all_messages(&self) -> Vec<(usize, ResolveMessage)> {
+        self.lines.iter().enumerate()
+            .flat_map(|(i, line)| line.messages.iter().map(move |x| (i, x.clone())))
+            .collect()
+    }
+
+    /// Names and functions used on a line
+    pub fn reads(&self, index: usize) -> &HashSet<String> {
+        &self.lines[index].reads
+    }
+
+    /// Names and functions assigned by resolving a line
+    pub fn defines(&self, index: usize) -> impl Iterator<Item = &String> {
+        self.lines[index].defines.keys()
+    }
+
+    /// Replaces the text of a line, returning messages of every line which was resolved again
+    pub fn set_line(&mut self, index: usize, text: String) -> Updates {
+        self.lines[index].set_text(text, self.base.number_mode.literals());
+        let dirty = self.neighbourhood(index, index);
+        self.update(*dirty.iter().next().unwrap(), dirty, HashSet::new())
+    }
+
+    /// Inserts a line before `index`, returning messages of every line which was resolved again
+    pub fn insert_line(&mut self, index: usize, text: String) -> Updates {
+        let group = self.next_group;
+        self.next_group += 1;
+        self.lines.insert(index, Line::new(text, group, self.base.number_mode.literals()));
+        let dirty = self.neighbourhood(index, index);
+        self.update(*dirty.iter().next().unwrap(), dirty, HashSet::new())
+    }
+
+    /// Removes a line, returning messages of every line which was resolved again
+    pub fn remove_line(&mut self, index: usize) -> Updates {
+        let removed = self.lines.remove(index);
+        // Lines which read the removed definitions need to be resolved again
+        let changed = removed.defines.keys().cloned().collect();
+        let mut dirty: BTreeSet<usize> = self.lines.iter().enumerate()
+            .filter(|(_, x)| x.group == removed.group)
+            .map(|(i, _)| i)
+            .collect();
+        if index > 0 {
+            dirty.append(&mut self.neighbourhood(index - 1, index - 1));
+        }
+        self.update(dirty.iter().next().map_or(index, |x| (*x).min(index)), dirty, changed)
+    }
+
+    /// Lines resolved together with the given lines and the line before them,
+    /// which can form a system of equations with an edited line
+    fn neighbourhood(&self, first: usize, last: usize) -> BTreeSet<usize> {
+        let first = first.saturating_sub(1);
+        let groups: HashSet<usize> = self.lines[first..=last].iter().map(|x| x.group).collect();
+        let mut out: BTreeSet<usize> = self.lines.iter().enumerate()
+            .filter(|(_, x)| groups.contains(&x.group))
+            .map(|(i, _)| i)
+            .collect();
+        out.insert(last);
+        out
+    }
+
+    /// Resolves the dirty lines and every line after `start` which reads a changed name
+    fn update(&mut self, start: usize, dirty: BTreeSet<usize>, mut changed: HashSet<String>) -> Updates {
+        // Lines before the first edit are unaffected, so their definitions are replayed without resolving them
+        self.resolver = self.base.clone();
+        for line in &self.lines[..start] {
+            self.resolver.namespace.extend(line.defines.iter().map(|(name, value)| (name.clone(), value.clone())));
+        }
+
+        let mut updates = Updates::new();
+        for index in start..self.lines.len() {
+            let line = &self.lines[index];
+            // Lines after an unfinished system of equations could complete it
+            if !dirty.contains(&index) && !self.resolver.has_pending_system() && line.reads.is_disjoint(&changed) {
+                for (name, value) in &line.defines {
+                    // A line which did not change shadows the earlier definition
+                    changed.remove(name);
+                    self.resolver.namespace.insert(name.clone(), value.clone());
+                }
+                continue;
+            }
+
+            let before = self.resolver.namespace.clone();
+            let messages = match &line.node {
+                Ok(node) => self.resolver.resolve_next(index, node.clone()),
+                Err(err) => {
+                    let mut out = self.resolver.finish();
+                    out.push((index, err.clone()));
+                    out
+                }
+            };
+            let mut members: BTreeSet<usize> = messages.iter().map(|(i, _)| *i).collect();
+            // A line waiting for the rest of its system is recorded once the system is resolved
+            if !self.resolver.has_pending_system() {
+                members.insert(index);
+            }
+            self.record(&before, members, messages, &mut changed, &mut updates);
+        }
+
+        let before = self.resolver.namespace.clone();
+        let messages = self.resolver.finish();
+        let members = messages.iter().map(|(i, _)| *i).collect();
+        self.record(&before, members, messages, &mut changed, &mut updates);
+
+        updates
+    }
+
+    /// Stores the result of a resolving step in its lines, noting names whose values changed
+    fn record(&mut self, before: &HashMap<String, NamespaceElement>, members: BTreeSet<usize>, messages: Vec<(usize, ResolveMessage)>,
+              changed: &mut HashSet<String>, updates: &mut Updates) {
+        let defines: HashMap<String, NamespaceElement> = self.resolver.namespace.iter()
+            .filter(|(name, value)| before.get(*name) != Some(value))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        let group = self.next_group;
+        self.next_group += 1;
+
+        for index in members {
+            let line = &mut self.lines[index];
+            changed.extend(line.defines.iter().filter(|(name, value)| defines.get(*name) != Some(value)).map(|(name, _)| name.clone()));
+            changed.extend(defines.iter().filter(|(name, value)| line.defines.get(*name) != Some(value)).map(|(name, _)| name.clone()));
+            line.defines = defines.clone();
+            line.group = group;
+            line.messages = messages.iter().filter(|(i, _)| *i == index).map(|(_, x)| x.clone()).collect();
+            updates.insert(index, line.messages.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resolver::resolve_message::ResolveMessage;
+
+    use super::Document;
+
+    fn document(lines: &[&str]) -> Document {
+        Document::new(lines.iter().map(|x| x.to_string()).collect())
+    }
+
+    #[test]
+    fn document_resolves_only_dependents() {
+        let mut doc = document(&["a = 2", "b = 10", "a * 3", "b + 1", "a + b"]);
+        assert_eq!(doc.messages(2), &[ResolveMessage::output("? = 6")]);
+
+        let updates = doc.set_line(0, "a = 5".into());
+        assert_eq!(updates.keys().copied().collect::<Vec<_>>(), vec![0, 2, 4]);
+        assert_eq!(updates[&2], vec![ResolveMessage::output("? = 15")]);
+        assert_eq!(updates[&4], vec![ResolveMessage::output("? = 15")]);
+        assert_eq!(doc.messages(3), &[ResolveMessage::output("? = 11")]);
+    }
+
+    #[test]
+    fn document_follows_dependencies_transitively() {
+        let mut doc = document(&["a = 2", "b = a + 1", "c = 7", "b * 2"]);
+        assert_eq!(doc.messages(3), &[ResolveMessage::output("? = 6")]);
+        assert_eq!(doc.defines(1).collect::<Vec<_>>(), vec!["b"]);
+
+        let updates = doc.set_line(0, "a = 4".into());
+        assert_eq!(updates.keys().copied().collect::<Vec<_>>(), vec![0, 1, 3]);
+        assert_eq!(updates[&3], vec![ResolveMessage::output("? = 10")]);
+    }
+
+    #[test]
+    fn document_stops_at_unchanged_values() {
+        let mut doc = document(&["a = 3", "a + 1"]);
+        let updates = doc.set_line(0, "a = 1 + 2".into());
+        assert_eq!(updates.keys().copied().collect::<Vec<_>>(), vec![0]);
+    }
+
+    #[test]
+    fn document_inserts_and_removes_lines() {
+        let mut doc = document(&["a * 2", "b = 1"]);
+        assert!(doc.messages(0).iter().any(|x| x.content.contains("a")));
+
+        let updates = doc.insert_line(0, "a = 4".into());
+        assert_eq!(updates[&1], vec![ResolveMessage::output("? = 8")]);
+
+        let updates = doc.remove_line(0);
+        assert!(updates[&0].iter().any(|x| x.content.contains("a")));
+        assert_eq!(doc.len(), 2);
+    }
+
+    #[test]
+    fn document_resolves_functions_capturing_changed_names() {
+        let mut doc = document(&["a = 2", "let f(x) = a * x", "f(5)"]);
+        assert_eq!(doc.messages(2), &[ResolveMessage::output("? = 10")]);
+
+        let updates = doc.set_line(0, "a = 3".into());
+        assert_eq!(updates.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(updates[&2], vec![ResolveMessage::output("? = 15")]);
+    }
+
+    #[test]
+    fn document_resolves_systems_again() {
+        let mut doc = document(&["x + y = 10", "x - y = 2", "x * y"]);
+        assert_eq!(doc.messages(2), &[ResolveMessage::output("? = 24")]);
+
+        let updates = doc.set_line(1, "x - y = 4".into());
+        assert_eq!(updates[&0], vec![ResolveMessage::output("x = 7, y = 3")]);
+        assert_eq!(updates[&2], vec![ResolveMessage::output("? = 21")]);
+    }
+}
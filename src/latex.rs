@@ -1,6 +1,7 @@
 use crate::parser::parsers::is_implied_multiplication;
 use crate::parser::node::{ASTNode, ASTNodeType};
-use crate::tokenizer::{Token, Operation};
+use crate::resolver::{builtins::BOUND_BUILTINS, list_node_to_vec, trace::SolutionTrace};
+use crate::tokenizer::{Comparison, Logic, Token, Operation};
 
 use itertools::join;
 
@@ -11,6 +12,8 @@ impl ASTNode {
             ASTNodeType::Delimeter(token) => match token {
                 Token::Name(name) => name.into(),
                 Token::Number(num) => num.to_string(),
+                Token::Decimal(num) => num.to_string(),
+                Token::Boolean(value) => format!("\\mathrm{{{}}}", value),
                 Token::Operation(op) => match op {
                     Operation::Add => "+".into(),
                     Operation::Sub => "-".into(),
@@ -23,8 +26,78 @@ impl ASTNode {
                 Token::ClosingBracket => "]".into(),
                 _ => "".into()
             }
+            ASTNodeType::Unit(unit) => format!("\\mathrm{{{}}}", unit.name),
+            ASTNodeType::Quantity(quantity) => {
+                if quantity.dimension.is_dimensionless() {
+                    quantity.value.to_string()
+                } else {
+                    format!("{} \\mathrm{{{}}}", quantity.value, quantity.dimension)
+                }
+            }
+            ASTNodeType::Rational(rational) => {
+                if rational.is_integer() {
+                    rational.to_string()
+                } else {
+                    format!("\\frac{{{}}}{{{}}}", rational.numer(), rational.denom())
+                }
+            }
+            ASTNodeType::Complex(complex) => complex.to_string(),
+            ASTNodeType::Vector => {
+                // A vector of vectors is a matrix written row by row, a plain vector is a column
+                if self.children.iter().all(|x| x.node_type == ASTNodeType::Vector) {
+                    pmatrix(self.children.iter().map(|row| row.children.iter().map(|x| x.to_latex()).collect()).collect())
+                } else {
+                    pmatrix(self.children.iter().map(|x| vec![x.to_latex()]).collect())
+                }
+            }
+            ASTNodeType::Matrix(matrix) => pmatrix(matrix.to_rows().iter().map(|row| row.iter().map(|x| x.to_string()).collect()).collect()),
+            ASTNodeType::Function(name) if BOUND_BUILTINS.contains(&name.as_str()) && self.children.len() == 1 && list_node_to_vec(&self.children[0]).len() == 4 => {
+                let args = list_node_to_vec(&self.children[0]);
+                let body = match args[0].node_type {
+                    ASTNodeType::Sum | ASTNodeType::Difference => format!("({})", args[0].to_latex()),
+                    _ => args[0].to_latex()
+                };
+                let (variable, a, b) = (args[1].to_latex(), args[2].to_latex(), args[3].to_latex());
+                match name.as_str() {
+                    "integrate" => format!("\\int_{{{}}}^{{{}}} {} \\, d{}", a, b, body, variable),
+                    "sum" => format!("\\sum_{{{}={}}}^{{{}}} {}", variable, a, b, body),
+                    _ => format!("\\prod_{{{}={}}}^{{{}}} {}", variable, a, b, body),
+                }
+            }
+            ASTNodeType::Comparison(comparison) if self.children.len() == 2 => {
+                let symbol = match comparison {
+                    Comparison::Less => "<",
+                    Comparison::Greater => ">",
+                    Comparison::LessEqual => "\\le",
+                    Comparison::GreaterEqual => "\\ge",
+                    Comparison::NotEqual => "\\neq",
+                };
+                format!("{} {} {}", self.children[0].to_latex(), symbol, self.children[1].to_latex())
+            }
+            ASTNodeType::Logic(logic) => {
+                // Operations binding looser than their parent are parenthesised, ex. a \land (b \lor c)
+                let operand = |x: &ASTNode| match (&x.node_type, logic) {
+                    (ASTNodeType::Logic(Logic::Or | Logic::Xor), _)
+                    | (ASTNodeType::Logic(Logic::And) | ASTNodeType::Comparison(_) | ASTNodeType::Equality, Logic::Not) => format!("({})", x.to_latex()),
+                    _ => x.to_latex(),
+                };
+                match (logic, self.children.as_slice()) {
+                    (Logic::Not, [a]) => format!("\\neg {}", operand(a)),
+                    (Logic::And, [a, b]) => format!("{} \\land {}", operand(a), operand(b)),
+                    (Logic::Or, [a, b]) => format!("{} \\lor {}", operand(a), operand(b)),
+                    (Logic::Xor, [a, b]) => format!("{} \\oplus {}", operand(a), operand(b)),
+                    _ => "".into(),
+                }
+            }
+            ASTNodeType::Conversion => {
+                if self.children.len() == 2 {
+                    format!("{} \\to {}", self.children[0].to_latex(), self.children[1].to_latex())
+                } else {
+                    "".into()
+                }
+            }
             ASTNodeType::Empty => {
-                if self.children.len() == 0 {
+                if self.children.is_empty() {
                     "".into()
                 } else if self.children.len() == 1 {
                     self.children.first().unwrap().to_latex()
@@ -69,6 +142,8 @@ impl ASTNode {
                         | ASTNodeType::Equality
                         | ASTNodeType::Function(_)
                         | ASTNodeType::Quotient
+                        | ASTNodeType::Vector
+                        | ASTNodeType::Matrix(_)
                         | ASTNodeType::Empty => self.children[0].to_latex(),
                         _ => format!("({})", self.children[0].to_latex())
                     };
@@ -77,10 +152,16 @@ impl ASTNode {
                         | ASTNodeType::Equality
                         | ASTNodeType::Function(_)
                         | ASTNodeType::Quotient
+                        | ASTNodeType::Unit(_)
+                        | ASTNodeType::Vector
+                        | ASTNodeType::Matrix(_)
                         | ASTNodeType::Empty => self.children[1].to_latex(),
                         _ => format!("({})", self.children[1].to_latex())
                     };
-                    if is_implied_multiplication(&self.children[0], &self.children[1]) {
+                    // Simplified expressions negate by multiplying with -1, ex. -x
+                    if self.children[0].node_type == ASTNodeType::Delimeter(Token::Number(-1.)) {
+                        format!("-{}", b)
+                    } else if is_implied_multiplication(&self.children[0], &self.children[1]) {
                         format!("{} {}", a, b)
                     } else {
                         format!("{}*{}", a, b)
@@ -156,3 +237,24 @@ impl ASTNode {
 
     }
 }
+
+impl SolutionTrace {
+    /// Renders the steps as a derivation aligned on the equals signs, with the operations on the side
+    pub fn to_latex(&self) -> String {
+        let equation = |x: &ASTNode| match x.children.as_slice() {
+            [left, right] => format!("{} &= {}", left.to_latex(), right.to_latex()),
+            _ => x.to_latex(),
+        };
+        let mut lines = vec![equation(&self.equation)];
+        for step in &self.steps {
+            lines.push(format!("{} && \\text{{{}}}", equation(&step.equation), step.operation));
+        }
+        format!("\\begin{{align*}}\n{}\n\\end{{align*}}", lines.join(" \\\\\n"))
+    }
+}
+
+/// Renders rows of elements as a matrix in parentheses
+fn pmatrix(rows: Vec<Vec<String>>) -> String {
+    let rows: Vec<String> = rows.iter().map(|row| row.join(" & ")).collect();
+    format!("\\begin{{pmatrix}}{}\\end{{pmatrix}}", rows.join(" \\\\ "))
+}
@@ -2,6 +2,9 @@ pub mod tokenizer;
 pub mod parser;
 pub mod latex;
 pub mod resolver;
+pub mod units;
+pub mod number;
+pub mod document;
 
 use resolver::resolve_message::ResolveMessage;
 
@@ -22,42 +25,41 @@ impl LRobotError {
                 parser::ParseError::UnmatchedOpeningBracket => ResolveMessage::error("Unmatched opening bracket"),
                 parser::ParseError::UnmatchedClosingBracket => ResolveMessage::error("Unmatched closing bracket"),
                 parser::ParseError::WrongBracket => ResolveMessage::error("Wrong bracket"),
+                parser::ParseError::UnknownUnit(name) => ResolveMessage::error(&format!("Unknown unit: {}", name)),
+                parser::ParseError::InvalidUnit => ResolveMessage::error("Invalid unit"),
             },
         }
     }
 }
 
 pub fn resolve_lines(lines: Vec<String>) -> Vec<(usize, ResolveMessage)> {
-    let mut resolver = resolver::Resolver::new();
+    resolve_lines_with(&mut resolver::Resolver::new(), lines)
+}
 
-    lines.iter()
-        .map(|line| {
-            match tokenizer::tokenize(line) {
-                Ok(x) => Ok(x),
-                Err(err) => Err(LRobotError::TokenizeError(err)),
-            }
-        })
-        // .filter(|(_, tokens)| tokens.len() != 0)
-        .map(|tokens| {
-            match tokens {
-                Ok(tokens) => {
-                    match parser::parsers::parse(&tokens) {
-                        Ok(x) => Ok(x),
-                        Err(err) => Err(LRobotError::ParsingError(err)),
-                    }
-                },
-                Err(x) => Err(x),
-            }
-        })
+/// Resolves lines using an existing resolver, ex. one with physics constants defined
+pub fn resolve_lines_with(resolver: &mut resolver::Resolver, lines: Vec<String>) -> Vec<(usize, ResolveMessage)> {
+    let literals = resolver.number_mode.literals();
+    let (mut out, parsed) = lines.iter()
+        .map(|line| parse_line(line, literals))
         .enumerate()
-        .map(|(line_num, output)| {
+        // Lines which parsed are resolved together, so that equations on consecutive lines can form a system
+        .fold((Vec::new(), Vec::new()), |(mut out, mut parsed), (line_num, output)| {
             match output {
-                Ok(x) => resolver.resolve_line(x).into_iter().map(|y| (line_num + 1, y)).collect(),
-                Err(x) => vec![(line_num + 1, x.to_resolve_message())],
+                Ok(x) => parsed.push((line_num + 1, x)),
+                Err(x) => {
+                    out.append(&mut resolver.resolve(std::mem::take(&mut parsed)));
+                    out.push((line_num + 1, x.to_resolve_message()));
+                }
             }
-        })
-        .flatten()
-        .collect()
+            (out, parsed)
+        });
+    out.append(&mut resolver.resolve(parsed));
+    out
+}
+
+fn parse_line(line: &str, literals: tokenizer::NumberLiterals) -> Result<parser::node::ASTNode, LRobotError> {
+    let tokens = tokenizer::tokenize_spanned_with(line, literals).map_err(LRobotError::TokenizeError)?;
+    parser::parsers::parse_spanned(&tokens).map_err(LRobotError::ParsingError)
 }
 
 pub fn git_hash() -> String {
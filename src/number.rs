@@ -0,0 +1,5 @@
+pub mod bigint;
+pub mod complex;
+pub mod decimal;
+pub mod matrix;
+pub mod rational;
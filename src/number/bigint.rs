@@ -0,0 +1,333 @@
+use std::{cmp::Ordering, fmt, ops::{Add, Mul, Neg, Sub}, str::FromStr};
+
+/// Each limb holds 9 decimal digits, which keeps printing and parsing simple
+const BASE: u64 = 1_000_000_000;
+const BASE_DIGITS: usize = 9;
+
+/// Arbitrary-precision integer, stored as a sign and limbs in base 10^9, least significant first
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    /// No trailing zero limbs, zero has no limbs
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        BigInt::default()
+    }
+
+    pub fn one() -> Self {
+        BigInt::from(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> Self {
+        BigInt { negative: false, limbs: self.limbs.clone() }
+    }
+
+    /// 10 raised to the given power
+    pub fn pow10(exp: usize) -> Self {
+        let mut limbs = vec![0; exp / BASE_DIGITS];
+        limbs.push(10u32.pow((exp % BASE_DIGITS) as u32));
+        BigInt { negative: false, limbs }
+    }
+
+    pub fn pow(&self, mut exp: u32) -> Self {
+        let mut base = self.clone();
+        let mut out = BigInt::one();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                out = &out * &base;
+            }
+            base = &base * &base;
+            exp >>= 1;
+        }
+        out
+    }
+
+    /// Number of decimal digits, 0 has one digit
+    pub fn digits(&self) -> usize {
+        match self.limbs.last() {
+            Some(top) => (self.limbs.len() - 1) * BASE_DIGITS + top.to_string().len(),
+            None => 1,
+        }
+    }
+
+    /// Quotient and remainder of a division truncated towards zero, `None` when dividing by zero
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_limbs(&self.limbs, &other.limbs);
+        Some((
+            BigInt::from_limbs(self.negative != other.negative, quotient),
+            BigInt::from_limbs(self.negative, remainder),
+        ))
+    }
+
+    /// Greatest common divisor, always non-negative
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let (mut a, mut b) = (self.abs(), other.abs());
+        while !b.is_zero() {
+            let (_, remainder) = a.div_rem(&b).unwrap();
+            a = b;
+            b = remainder;
+        }
+        a
+    }
+
+    /// Closest float, infinite when the integer is out of range
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap()
+    }
+
+    fn from_limbs(negative: bool, mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigInt { negative: negative && !limbs.is_empty(), limbs }
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let mut magnitude = value.unsigned_abs();
+        let mut limbs = Vec::new();
+        while magnitude > 0 {
+            limbs.push((magnitude % BASE) as u32);
+            magnitude /= BASE;
+        }
+        BigInt::from_limbs(value < 0, limbs)
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = String;
+
+    /// Parses an optionally signed string of decimal digits
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        if digits.is_empty() || !digits.bytes().all(|x| x.is_ascii_digit()) {
+            return Err(format!("Invalid integer: {}", s));
+        }
+        let limbs = digits.as_bytes()
+            .rchunks(BASE_DIGITS)
+            .map(|chunk| chunk.iter().fold(0, |acc, x| acc * 10 + (x - b'0') as u32))
+            .collect();
+        Ok(BigInt::from_limbs(negative, limbs))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut limbs = self.limbs.iter().rev();
+        match limbs.next() {
+            Some(top) => {
+                if self.negative {
+                    write!(f, "-")?;
+                }
+                write!(f, "{}", top)?;
+                for limb in limbs {
+                    write!(f, "{:09}", limb)?;
+                }
+                Ok(())
+            }
+            None => write!(f, "0"),
+        }
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_limbs(&self.limbs, &other.limbs),
+            (true, true) => cmp_limbs(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_limbs(!self.negative, self.limbs.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_limbs(self.negative, add_limbs(&self.limbs, &other.limbs));
+        }
+        // Signs differ, the smaller magnitude is subtracted from the larger one
+        match cmp_limbs(&self.limbs, &other.limbs) {
+            Ordering::Less => BigInt::from_limbs(other.negative, sub_limbs(&other.limbs, &self.limbs)),
+            _ => BigInt::from_limbs(self.negative, sub_limbs(&self.limbs, &other.limbs)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut limbs = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let x = limbs[i + j] + *a as u64 * *b as u64 + carry;
+                limbs[i + j] = x % BASE;
+                carry = x / BASE;
+            }
+            limbs[i + other.limbs.len()] += carry;
+        }
+        BigInt::from_limbs(self.negative != other.negative, limbs.into_iter().map(|x| x as u32).collect())
+    }
+}
+
+fn cmp_limbs(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_limbs(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let x = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        out.push((x % BASE) as u32);
+        carry = x / BASE;
+    }
+    out.push(carry as u32);
+    out
+}
+
+/// Subtracts magnitudes, `a` must not be smaller than `b`
+fn sub_limbs(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, x) in a.iter().enumerate() {
+        let y = *b.get(i).unwrap_or(&0) as i64 + borrow;
+        let mut x = *x as i64 - y;
+        borrow = 0;
+        if x < 0 {
+            x += BASE as i64;
+            borrow = 1;
+        }
+        out.push(x as u32);
+    }
+    out
+}
+
+fn mul_limb(a: &[u32], b: u32) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0;
+    for x in a {
+        let x = *x as u64 * b as u64 + carry;
+        out.push((x % BASE) as u32);
+        carry = x / BASE;
+    }
+    out.push(carry as u32);
+    while out.last() == Some(&0) {
+        out.pop();
+    }
+    out
+}
+
+/// Long division of magnitudes, one limb of the quotient at a time
+fn div_rem_limbs(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let mut quotient = vec![0; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for i in (0..a.len()).rev() {
+        remainder.insert(0, a[i]);
+        while remainder.last() == Some(&0) {
+            remainder.pop();
+        }
+        // Largest digit such that b * digit <= remainder, found by bisection
+        let (mut low, mut high) = (0, BASE as u32 - 1);
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if cmp_limbs(&mul_limb(b, mid), &remainder) == Ordering::Greater {
+                high = mid - 1;
+            } else {
+                low = mid;
+            }
+        }
+        quotient[i] = low;
+        remainder = sub_limbs(&remainder, &mul_limb(b, low));
+        while remainder.last() == Some(&0) {
+            remainder.pop();
+        }
+    }
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BigInt;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn bigint_arithmetic() {
+        let a = big("123456789012345678901234567890");
+        let b = big("-987654321098765432109876543210");
+        assert_eq!((&a + &b).to_string(), "-864197532086419753208641975320");
+        assert_eq!((&a - &b).to_string(), "1111111110111111111011111111100");
+        assert_eq!((&a * &b).to_string(), "-121932631137021795226185032733622923332237463801111263526900");
+        assert_eq!(BigInt::from(2).pow(100).to_string(), "1267650600228229401496703205376");
+    }
+
+    #[test]
+    fn bigint_division() {
+        let a = big("1267650600228229401496703205377");
+        let (q, r) = a.div_rem(&BigInt::from(1_000_000_007)).unwrap();
+        assert_eq!(&(&q * &BigInt::from(1_000_000_007)) + &r, a);
+        assert!(r < BigInt::from(1_000_000_007));
+
+        let (q, r) = big("-7").div_rem(&big("2")).unwrap();
+        assert_eq!((q, r), (big("-3"), big("-1")));
+        assert!(a.div_rem(&BigInt::zero()).is_none());
+        assert_eq!(big("1071").gcd(&big("-462")), big("21"));
+    }
+
+    #[test]
+    fn bigint_parses_and_converts() {
+        assert_eq!(big("-000123").to_string(), "-123");
+        assert_eq!(big("-0"), BigInt::zero());
+        assert!("12a".parse::<BigInt>().is_err());
+        assert_eq!(big("1000000000").digits(), 10);
+        assert_eq!(BigInt::pow10(12), big("1000000000000"));
+        assert_eq!(big("123456789012345678901234567890").to_f64(), 1.2345678901234568e29);
+    }
+}
@@ -0,0 +1,189 @@
+use std::{fmt, ops::{Add, Div, Mul, Neg, Sub}};
+
+use crate::units::format_number;
+
+/// Complex number with floating point parts
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+/// Parts smaller than this, relative to the other part, are rounding errors, ex. the imaginary part of exp(i pi)
+const NEGLIGIBLE: f64 = 1e-15;
+
+impl Complex {
+    pub const I: Complex = Complex { re: 0., im: 1. };
+
+    pub fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    pub fn real(re: f64) -> Self {
+        Complex { re, im: 0. }
+    }
+
+    pub fn is_real(&self) -> bool {
+        self.im == 0.
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.re.is_finite() && self.im.is_finite()
+    }
+
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// Angle from the positive real axis, in (-pi, pi]
+    pub fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn conj(self) -> Complex {
+        Complex::new(self.re, -self.im)
+    }
+
+    pub fn exp(self) -> Complex {
+        let (sin, cos) = self.im.sin_cos();
+        Complex::new(cos, sin) * self.re.exp()
+    }
+
+    /// Principal logarithm
+    pub fn ln(self) -> Complex {
+        Complex::new(self.abs().ln(), self.arg())
+    }
+
+    /// Principal square root
+    pub fn sqrt(self) -> Complex {
+        let abs = self.abs();
+        let re = ((abs + self.re) / 2.).sqrt();
+        let im = ((abs - self.re) / 2.).sqrt();
+        Complex::new(re, if self.im < 0. { -im } else { im })
+    }
+
+    /// Principal power, integer exponents are calculated by multiplication so that i^2 is exactly -1
+    pub fn pow(self, exp: Complex) -> Complex {
+        if exp.is_real() && exp.re.fract() == 0. && exp.re.abs() <= u32::MAX as f64 {
+            let (mut base, mut n, mut out) = (self, exp.re.abs() as u32, Complex::real(1.));
+            while n > 0 {
+                if n & 1 == 1 {
+                    out = out * base;
+                }
+                base = base * base;
+                n >>= 1;
+            }
+            return if exp.re < 0. { Complex::real(1.) / out } else { out };
+        }
+        if self == Complex::default() {
+            return Complex::default();
+        }
+        (self.ln() * exp).exp()
+    }
+
+    /// Drops a part which is negligible compared to the other one
+    pub fn clean(self) -> Complex {
+        let scale = self.re.abs().max(self.im.abs());
+        let clean = |x: f64| if x.abs() < scale * NEGLIGIBLE { 0. } else { x };
+        Complex::new(clean(self.re), clean(self.im))
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(self.re * other.re - self.im * other.im, self.re * other.im + self.im * other.re)
+    }
+}
+
+impl Mul<f64> for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: f64) -> Complex {
+        Complex::new(self.re * other, self.im * other)
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+
+    fn div(self, other: Complex) -> Complex {
+        let denominator = other.re * other.re + other.im * other.im;
+        Complex::new(
+            (self.re * other.re + self.im * other.im) / denominator,
+            (self.im * other.re - self.re * other.im) / denominator,
+        )
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+
+    fn neg(self) -> Complex {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+/// Formats like 3 + 4i, -i or 2.5
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let imaginary = if self.im.abs() == 1. { "i".to_string() } else { format!("{}i", format_number(self.im.abs())) };
+        if self.im == 0. {
+            write!(f, "{}", format_number(self.re))
+        } else if self.re == 0. {
+            write!(f, "{}{}", if self.im < 0. { "-" } else { "" }, imaginary)
+        } else {
+            write!(f, "{} {} {}", format_number(self.re), if self.im < 0. { "-" } else { "+" }, imaginary)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::Complex;
+
+    #[test]
+    fn complex_arithmetic() {
+        let a = Complex::new(3., 4.);
+        assert_eq!(a * a.conj(), Complex::real(25.));
+        assert_eq!(a / Complex::I, Complex::new(4., -3.));
+        assert_eq!(Complex::I.pow(Complex::real(2.)), Complex::real(-1.));
+        assert_eq!(a.abs(), 5.);
+    }
+
+    #[test]
+    fn complex_functions_use_principal_values() {
+        assert_eq!(Complex::real(-4.).sqrt(), Complex::new(0., 2.));
+        assert_eq!(Complex::real(-1.).ln(), Complex::new(0., PI));
+        assert_eq!((Complex::I * PI).exp().clean(), Complex::real(-1.));
+        assert_eq!(Complex::real(-8.).pow(Complex::real(1. / 3.)).clean().to_string(), "1 + 1.732050807568877i");
+    }
+
+    #[test]
+    fn complex_display() {
+        assert_eq!(Complex::new(3., 4.).to_string(), "3 + 4i");
+        assert_eq!(Complex::new(3., -4.).to_string(), "3 - 4i");
+        assert_eq!(Complex::new(0., -1.).to_string(), "-i");
+        assert_eq!(Complex::new(0., 2.5).to_string(), "2.5i");
+        assert_eq!(Complex::new(-2., 0.).to_string(), "-2");
+    }
+}
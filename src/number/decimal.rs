@@ -0,0 +1,241 @@
+use std::{fmt, ops::{Add, Mul, Neg, Sub}, str::FromStr};
+
+use super::bigint::BigInt;
+
+/// Arbitrary-precision decimal `mantissa * 10^-scale`.
+/// Sums, differences and products are exact, divisions are rounded to a number of significant digits.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Decimal {
+    /// Never ends with a zero digit, so that every value has one representation
+    mantissa: BigInt,
+    scale: i64,
+}
+
+impl Decimal {
+    fn new(mut mantissa: BigInt, mut scale: i64) -> Self {
+        if mantissa.is_zero() {
+            return Decimal::default();
+        }
+        let ten = BigInt::from(10);
+        loop {
+            match mantissa.div_rem(&ten) {
+                Some((quotient, remainder)) if remainder.is_zero() => {
+                    mantissa = quotient;
+                    scale -= 1;
+                }
+                _ => break,
+            }
+        }
+        Decimal { mantissa, scale }
+    }
+
+    pub fn integer(value: BigInt) -> Self {
+        Decimal::new(value, 0)
+    }
+
+    /// The decimal as the float is written, ex. 0.1 is exactly 0.1
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+        // Display prints the shortest decimal which reads back as the same float, without an exponent
+        value.to_string().parse().ok()
+    }
+
+    pub fn mantissa(&self) -> &BigInt {
+        &self.mantissa
+    }
+
+    /// Number of digits after the decimal point, negative for multiples of powers of 10
+    pub fn scale(&self) -> i64 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+
+    /// Number of digits written out, ex. 4 for 0.001 and 12.5
+    pub fn digits(&self) -> usize {
+        let digits = self.mantissa.digits();
+        if self.scale <= 0 {
+            digits.saturating_add(self.scale.unsigned_abs() as usize)
+        } else {
+            digits.max((self.scale as usize).saturating_add(1))
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.scale <= 0
+    }
+
+    /// Rounds half to even, keeping at most `precision` significant digits
+    pub fn round(&self, precision: usize) -> Decimal {
+        let digits = self.mantissa.digits();
+        if digits <= precision {
+            return self.clone();
+        }
+        let dropped = digits - precision;
+        let (mut quotient, remainder) = self.mantissa.div_rem(&BigInt::pow10(dropped)).unwrap();
+        let twice = &remainder.abs() * &BigInt::from(2);
+        let half_comparison = twice.cmp(&BigInt::pow10(dropped));
+        let odd = !quotient.div_rem(&BigInt::from(2)).unwrap().1.is_zero();
+        if half_comparison.is_gt() || (half_comparison.is_eq() && odd) {
+            let step = if self.mantissa.is_negative() { BigInt::from(-1) } else { BigInt::one() };
+            quotient = &quotient + &step;
+        }
+        Decimal::new(quotient, self.scale - dropped as i64)
+    }
+
+    /// Quotient rounded to `precision` significant digits, `None` when dividing by zero
+    pub fn checked_div(&self, other: &Decimal, precision: usize) -> Option<Decimal> {
+        if other.is_zero() {
+            return None;
+        }
+        // Scale the dividend so that the integer quotient has more digits than needed
+        let extra = (precision + other.mantissa.digits() + 1).saturating_sub(self.mantissa.digits());
+        let (quotient, remainder) = (&self.mantissa * &BigInt::pow10(extra)).div_rem(&other.mantissa).unwrap();
+        // A nonzero remainder is kept as an extra digit, so that rounding can tell 0.5000...1 from 0.5
+        let sticky = match (remainder.is_zero(), self.mantissa.is_negative() != other.mantissa.is_negative()) {
+            (true, _) => BigInt::zero(),
+            (false, true) => BigInt::from(-1),
+            (false, false) => BigInt::one(),
+        };
+        let quotient = &(&quotient * &BigInt::from(10)) + &sticky;
+        Some(Decimal::new(quotient, self.scale - other.scale + extra as i64 + 1).round(precision))
+    }
+
+    /// Raises to an integer power, negative powers are divisions rounded to `precision` significant digits
+    pub fn checked_pow(&self, exp: i32, precision: usize) -> Option<Decimal> {
+        let power = Decimal::new(self.mantissa.pow(exp.unsigned_abs()), self.scale * exp.unsigned_abs() as i64);
+        if exp < 0 {
+            Decimal::integer(BigInt::one()).checked_div(&power, precision)
+        } else {
+            Some(power)
+        }
+    }
+
+    /// Mantissa of the same value with a larger scale
+    fn mantissa_at(&self, scale: i64) -> BigInt {
+        &self.mantissa * &BigInt::pow10((scale - self.scale) as usize)
+    }
+
+    /// Closest float to the decimal
+    pub fn to_f64(&self) -> f64 {
+        format!("{}e{}", self.mantissa, -self.scale).parse().unwrap()
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = String;
+
+    /// Parses a decimal literal like `-12.375` or `1.5e-3`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid decimal: {}", s);
+        let (digits, exponent) = match s.split_once(['e', 'E']) {
+            Some((digits, exponent)) => {
+                let magnitude = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+                if magnitude.is_empty() || !magnitude.bytes().all(|x| x.is_ascii_digit()) {
+                    return Err(invalid());
+                }
+                // Exponents past the range of i64 saturate, the resolver rejects decimals with that many digits
+                let saturated = if exponent.starts_with('-') { i64::MIN } else { i64::MAX };
+                (digits, exponent.parse::<i64>().unwrap_or(saturated))
+            }
+            None => (s, 0),
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let mantissa: BigInt = format!("{}{}", integer, fraction).parse().map_err(|_| invalid())?;
+        Ok(Decimal::new(mantissa, (fraction.len() as i64).saturating_sub(exponent)))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.mantissa.is_negative() { "-" } else { "" };
+        let digits = self.mantissa.abs().to_string();
+        if self.scale <= 0 {
+            return write!(f, "{}{}{}", sign, digits, "0".repeat(-self.scale as usize));
+        }
+        // Padded without a format width, which is limited to 65535
+        let digits = "0".repeat((self.scale as usize + 1).saturating_sub(digits.len())) + &digits;
+        let point = digits.len() - self.scale as usize;
+        write!(f, "{}{}.{}", sign, &digits[..point], &digits[point..])
+    }
+}
+
+impl Neg for &Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal::new(-&self.mantissa, self.scale)
+    }
+}
+
+impl Add for &Decimal {
+    type Output = Decimal;
+
+    fn add(self, other: &Decimal) -> Decimal {
+        let scale = self.scale.max(other.scale);
+        Decimal::new(&self.mantissa_at(scale) + &other.mantissa_at(scale), scale)
+    }
+}
+
+impl Sub for &Decimal {
+    type Output = Decimal;
+
+    fn sub(self, other: &Decimal) -> Decimal {
+        self + &-other
+    }
+}
+
+impl Mul for &Decimal {
+    type Output = Decimal;
+
+    fn mul(self, other: &Decimal) -> Decimal {
+        Decimal::new(&self.mantissa * &other.mantissa, self.scale + other.scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Decimal;
+
+    fn decimal(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn decimal_sums_do_not_drift() {
+        let total = (0..1000).fold(Decimal::default(), |acc, _| &acc + &decimal("0.10"));
+        assert_eq!(total, decimal("100"));
+        assert_eq!((&decimal("19.99") * &decimal("3")).to_string(), "59.97");
+        assert_eq!((&decimal("1234567890123456789.01") - &decimal("0.02")).to_string(), "1234567890123456788.99");
+        assert_eq!((&decimal("1.5") * &decimal("2")).to_string(), "3");
+    }
+
+    #[test]
+    fn decimal_division_rounds_half_to_even() {
+        assert_eq!(decimal("1").checked_div(&decimal("3"), 10).unwrap().to_string(), "0.3333333333");
+        assert_eq!(decimal("-2").checked_div(&decimal("3"), 5).unwrap().to_string(), "-0.66667");
+        assert_eq!(decimal("25").checked_div(&decimal("1000"), 1).unwrap().to_string(), "0.02");
+        assert_eq!(decimal("35").checked_div(&decimal("1000"), 1).unwrap().to_string(), "0.04");
+        assert_eq!(decimal("100").checked_div(&decimal("0.04"), 28).unwrap().to_string(), "2500");
+        assert!(decimal("1").checked_div(&decimal("0"), 28).is_none());
+    }
+
+    #[test]
+    fn decimal_powers_and_conversions() {
+        assert_eq!(decimal("1.1").checked_pow(3, 28).unwrap().to_string(), "1.331");
+        assert_eq!(decimal("2").checked_pow(-2, 28).unwrap().to_string(), "0.25");
+        assert_eq!(Decimal::from_f64(0.1).unwrap().to_string(), "0.1");
+        assert_eq!(decimal("-0.000125").to_f64(), -0.000125);
+        assert_eq!(decimal("-12.50").round(2).to_string(), "-12");
+        assert_eq!(decimal("1.5e-3").to_string(), "0.0015");
+        assert_eq!(decimal("2E3").to_string(), "2000");
+        assert_eq!(decimal("1e-70000").to_string().len(), 70002);
+        assert_eq!(decimal("1e-70000").digits(), 70001);
+        assert_eq!(decimal("12.5").digits(), 3);
+        assert_eq!(decimal("1e99999999999999999999").scale(), i64::MIN + 1);
+    }
+}
@@ -0,0 +1,239 @@
+use std::fmt;
+
+use crate::units::format_number;
+
+use super::{bigint::BigInt, rational::Rational};
+
+/// Matrix of real numbers stored row by row, vectors are matrices with a single column
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    /// Creates a matrix from its rows, `None` if there are no elements or the rows differ in length
+    pub fn from_rows(rows: Vec<Vec<f64>>) -> Option<Self> {
+        let cols = rows.first()?.len();
+        if cols == 0 || rows.iter().any(|x| x.len() != cols) {
+            return None;
+        }
+        Some(Matrix { rows: rows.len(), cols, data: rows.concat() })
+    }
+
+    /// Creates a column vector
+    pub fn vector(values: Vec<f64>) -> Self {
+        Matrix { rows: values.len(), cols: 1, data: values }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let data = (0..n * n).map(|x| if x / n == x % n { 1. } else { 0. }).collect();
+        Matrix { rows: n, cols: n, data }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        self.data[row * self.cols + col]
+    }
+
+    /// Elements of a matrix with a single row or column
+    pub fn as_vector(&self) -> Option<&[f64]> {
+        if self.rows == 1 || self.cols == 1 {
+            Some(&self.data)
+        } else {
+            None
+        }
+    }
+
+    /// Size in the rows x columns notation, ex. 2x3
+    pub fn size(&self) -> String {
+        format!("{}x{}", self.rows, self.cols)
+    }
+
+    pub fn map<F: Fn(f64) -> f64>(&self, f: F) -> Matrix {
+        Matrix { data: self.data.iter().map(|x| f(*x)).collect(), ..*self }
+    }
+
+    /// Combines the elements pairwise, `None` if the sizes differ
+    pub fn zip_with<F: Fn(f64, f64) -> f64>(&self, other: &Matrix, f: F) -> Option<Matrix> {
+        if (self.rows, self.cols) != (other.rows, other.cols) {
+            return None;
+        }
+        let data = self.data.iter().zip(&other.data).map(|(a, b)| f(*a, *b)).collect();
+        Some(Matrix { data, ..*self })
+    }
+
+    /// Matrix product, `None` if the columns of the left matrix do not match the rows of the right one
+    pub fn checked_mul(&self, other: &Matrix) -> Option<Matrix> {
+        if self.cols != other.rows {
+            return None;
+        }
+        let data = (0..self.rows * other.cols)
+            .map(|x| (0..self.cols).map(|k| self.get(x / other.cols, k) * other.get(k, x % other.cols)).sum())
+            .collect();
+        Some(Matrix { rows: self.rows, cols: other.cols, data })
+    }
+
+    /// Raises a square matrix to an integer power, negative powers invert it first
+    pub fn checked_pow(&self, exp: i32) -> Option<Matrix> {
+        if self.rows != self.cols {
+            return None;
+        }
+        let mut base = if exp < 0 { self.inverse()? } else { self.clone() };
+        let (mut n, mut out) = (exp.unsigned_abs(), Matrix::identity(self.rows));
+        while n > 0 {
+            if n & 1 == 1 {
+                out = out.checked_mul(&base)?;
+            }
+            base = base.checked_mul(&base)?;
+            n >>= 1;
+        }
+        Some(out)
+    }
+
+    pub fn transpose(&self) -> Matrix {
+        let data = (0..self.data.len()).map(|x| self.get(x % self.rows, x / self.rows)).collect();
+        Matrix { rows: self.cols, cols: self.rows, data }
+    }
+
+    /// Determinant of a square matrix
+    pub fn det(&self) -> Option<f64> {
+        if self.rows != self.cols {
+            return None;
+        }
+        // The determinant is the product of the pivots, every swap of rows changes its sign
+        let mut rows = self.to_rationals()?;
+        let mut det = Rational::integer(BigInt::one());
+        for col in 0..self.cols {
+            let pivot = match (col..self.rows).find(|x| !rows[*x][col].is_zero()) {
+                Some(pivot) => pivot,
+                None => return Some(0.),
+            };
+            if pivot != col {
+                rows.swap(pivot, col);
+                det = -&det;
+            }
+            det = &det * &rows[col][col];
+            eliminate(&mut rows, col, col + 1..self.rows);
+        }
+        Some(det.to_f64())
+    }
+
+    /// Inverse of a square matrix, `None` if it is singular
+    pub fn inverse(&self) -> Option<Matrix> {
+        if self.rows != self.cols {
+            return None;
+        }
+        // Gauss-Jordan elimination on the matrix augmented with the identity
+        let n = self.rows;
+        let identity = Matrix::identity(n).to_rationals()?;
+        let mut rows: Vec<Vec<Rational>> = self.to_rationals()?.into_iter().zip(identity).map(|(a, b)| [a, b].concat()).collect();
+        for col in 0..n {
+            let pivot = (col..n).find(|x| !rows[*x][col].is_zero())?;
+            rows.swap(pivot, col);
+            let divisor = rows[col][col].clone();
+            rows[col] = rows[col].iter().map(|x| x.checked_div(&divisor).unwrap()).collect();
+            eliminate(&mut rows, col, (0..n).filter(|x| *x != col));
+        }
+        Matrix::from_rows(rows.into_iter().map(|x| x[n..].iter().map(Rational::to_f64).collect()).collect())
+    }
+
+    /// Dot product of two vectors of the same length
+    pub fn dot(&self, other: &Matrix) -> Option<f64> {
+        match (self.as_vector(), other.as_vector()) {
+            (Some(a), Some(b)) if a.len() == b.len() => Some(a.iter().zip(b).map(|(a, b)| a * b).sum()),
+            _ => None,
+        }
+    }
+
+    /// Cross product of two vectors of length 3
+    pub fn cross(&self, other: &Matrix) -> Option<Matrix> {
+        match (self.as_vector(), other.as_vector()) {
+            (Some([a1, a2, a3]), Some([b1, b2, b3])) => Some(Matrix::vector(vec![
+                a2 * b3 - a3 * b2,
+                a3 * b1 - a1 * b3,
+                a1 * b2 - a2 * b1,
+            ])),
+            _ => None,
+        }
+    }
+
+    pub fn to_rows(&self) -> Vec<Vec<f64>> {
+        self.data.chunks(self.cols).map(|x| x.to_vec()).collect()
+    }
+
+    /// Rows of exact fractions, elimination with them does not accumulate rounding errors, ex. the inverse of ((1, 2), (3, 4)) has integers
+    fn to_rationals(&self) -> Option<Vec<Vec<Rational>>> {
+        self.to_rows().iter().map(|row| row.iter().map(|x| Rational::from_f64(*x)).collect()).collect()
+    }
+}
+
+/// Subtracts multiples of the pivot row, so that the column is zero in the given rows
+fn eliminate<I: Iterator<Item = usize>>(rows: &mut [Vec<Rational>], col: usize, targets: I) {
+    let pivot = rows[col].clone();
+    for row in targets {
+        let factor = rows[row][col].checked_div(&pivot[col]).unwrap();
+        for i in col..pivot.len() {
+            rows[row][i] = &rows[row][i] - &(&factor * &pivot[i]);
+        }
+    }
+}
+
+/// Formats vectors like (1, 2, 3) and matrices row by row like ((1, 2), (3, 4)), the same way they are written
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |x: &[f64]| x.iter().map(|x| format_number(*x)).collect::<Vec<String>>().join(", ");
+        if self.cols == 1 {
+            return write!(f, "({})", join(&self.data));
+        }
+        let rows: Vec<String> = self.data.chunks(self.cols).map(|x| format!("({})", join(x))).collect();
+        write!(f, "({})", rows.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Matrix;
+
+    fn matrix(rows: &[&[f64]]) -> Matrix {
+        Matrix::from_rows(rows.iter().map(|x| x.to_vec()).collect()).unwrap()
+    }
+
+    #[test]
+    fn matrix_arithmetic() {
+        let a = matrix(&[&[1., 2.], &[3., 4.]]);
+        assert_eq!(a.checked_mul(&Matrix::vector(vec![1., 1.])), Some(Matrix::vector(vec![3., 7.])));
+        assert_eq!(a.checked_pow(2), Some(matrix(&[&[7., 10.], &[15., 22.]])));
+        assert_eq!(a.transpose(), matrix(&[&[1., 3.], &[2., 4.]]));
+        assert_eq!(a.checked_mul(&matrix(&[&[1., 2., 3.]])), None);
+        assert_eq!(Matrix::from_rows(vec![vec![1., 2.], vec![3.]]), None);
+    }
+
+    #[test]
+    fn matrix_determinant_and_inverse() {
+        let a = matrix(&[&[0., 2., 1.], &[1., 1., 0.], &[3., 0., 1.]]);
+        assert_eq!(a.det(), Some(-5.));
+        assert_eq!(a.inverse(), Some(matrix(&[&[-0.2, 0.4, 0.2], &[0.2, 0.6, -0.2], &[0.6, -1.2, 0.4]])));
+        assert_eq!(matrix(&[&[1., 2.], &[3., 4.]]).inverse(), Some(matrix(&[&[-2., 1.], &[1.5, -0.5]])));
+        assert_eq!(matrix(&[&[1., 2.], &[2., 4.]]).inverse(), None);
+        assert_eq!(matrix(&[&[1., 2.], &[2., 4.]]).det(), Some(0.));
+    }
+
+    #[test]
+    fn vector_products_and_display() {
+        let x = Matrix::vector(vec![1., 0., 0.]);
+        let y = Matrix::vector(vec![0., 1., 0.]);
+        assert_eq!(x.cross(&y), Some(Matrix::vector(vec![0., 0., 1.])));
+        assert_eq!(x.dot(&y), Some(0.));
+        assert_eq!(x.to_string(), "(1, 0, 0)");
+        assert_eq!(matrix(&[&[1., 2.5], &[-3., 4.]]).to_string(), "((1, 2.5), (-3, 4))");
+    }
+}
@@ -0,0 +1,230 @@
+use std::{fmt, ops::{Add, Mul, Neg, Sub}};
+
+use super::bigint::BigInt;
+
+/// Exact fraction of arbitrary-precision integers, always in lowest terms with a positive denominator
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numer: BigInt,
+    denom: BigInt,
+}
+
+impl Rational {
+    /// Creates a fraction in lowest terms, `None` if the denominator is zero
+    pub fn new(numer: BigInt, denom: BigInt) -> Option<Self> {
+        if denom.is_zero() {
+            return None;
+        }
+        let gcd = numer.gcd(&denom);
+        let (mut numer, mut denom) = (numer.div_rem(&gcd).unwrap().0, denom.div_rem(&gcd).unwrap().0);
+        if denom.is_negative() {
+            numer = -&numer;
+            denom = -&denom;
+        }
+        Some(Rational { numer, denom })
+    }
+
+    pub fn integer(value: BigInt) -> Self {
+        Rational { numer: value, denom: BigInt::one() }
+    }
+
+    /// The exact value of a float as it is written, ex. 0.1 is 1/10 and not the nearest binary fraction
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+        // Display prints the shortest decimal which reads back as the same float, without an exponent
+        Rational::from_decimal_str(&value.to_string())
+    }
+
+    /// The fraction closest to a float with a denominator of at most `max_denom`, found by continued fractions,
+    /// ex. 0.3333333333333333 is 1/3. `None` if no such fraction is within the rounding error of the float.
+    pub fn approximate(value: f64, max_denom: i64) -> Option<Self> {
+        if !value.is_finite() || value.abs() > 1e12 {
+            return None;
+        }
+        // The last two convergents h / k
+        let (mut h, mut h_prev, mut k, mut k_prev) = (1i64, 0i64, 0i64, 1i64);
+        let mut rest = value;
+        loop {
+            let a = rest.floor();
+            let (h_next, k_next) = ((a as i64).checked_mul(h)?.checked_add(h_prev)?, (a as i64).checked_mul(k)?.checked_add(k_prev)?);
+            if k_next > max_denom {
+                return None;
+            }
+            (h_prev, h, k_prev, k) = (h, h_next, k, k_next);
+            if (h as f64 / k as f64 - value).abs() <= 4. * f64::EPSILON * value.abs() {
+                return Rational::new(BigInt::from(h), BigInt::from(k));
+            }
+            rest = 1. / (rest - a);
+        }
+    }
+
+    /// Parses a decimal literal like `-12.375`
+    pub fn from_decimal_str(s: &str) -> Option<Self> {
+        let (integer, fraction) = s.split_once('.').unwrap_or((s, ""));
+        let numer: BigInt = format!("{}{}", integer, fraction).parse().ok()?;
+        Rational::new(numer, BigInt::pow10(fraction.len()))
+    }
+
+    pub fn numer(&self) -> &BigInt {
+        &self.numer
+    }
+
+    pub fn denom(&self) -> &BigInt {
+        &self.denom
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == BigInt::one()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numer.is_zero()
+    }
+
+    /// `None` when dividing by zero
+    pub fn checked_div(&self, other: &Rational) -> Option<Rational> {
+        Rational::new(&self.numer * &other.denom, &self.denom * &other.numer)
+    }
+
+    /// Raises to an integer power, `None` for negative powers of zero
+    pub fn checked_pow(&self, exp: i32) -> Option<Rational> {
+        let (numer, denom) = (self.numer.pow(exp.unsigned_abs()), self.denom.pow(exp.unsigned_abs()));
+        if exp < 0 {
+            Rational::new(denom, numer)
+        } else {
+            Rational::new(numer, denom)
+        }
+    }
+
+    /// Closest float to the fraction
+    pub fn to_f64(&self) -> f64 {
+        // Scale the fraction so that the integer quotient has about 20 significant digits, which parse correctly rounded
+        let shift = self.numer.digits() as i64 - self.denom.digits() as i64 - 20;
+        let (numer, denom) = if shift < 0 {
+            (&self.numer * &BigInt::pow10(-shift as usize), self.denom.clone())
+        } else {
+            (self.numer.clone(), &self.denom * &BigInt::pow10(shift as usize))
+        };
+        let (quotient, _) = numer.div_rem(&denom).unwrap();
+        format!("{}e{}", quotient, shift).parse().unwrap()
+    }
+
+    /// Exact decimal representation, `None` if the decimal expansion does not terminate, ex. for 1/3
+    pub fn to_decimal_string(&self) -> Option<String> {
+        // Terminating fractions have denominators of the form 2^a * 5^b, which divide 10^max(a, b)
+        let mut rest = self.denom.clone();
+        let mut places = 0;
+        for factor in [BigInt::from(2), BigInt::from(5)] {
+            let mut count = 0;
+            loop {
+                match rest.div_rem(&factor) {
+                    Some((quotient, remainder)) if remainder.is_zero() => {
+                        rest = quotient;
+                        count += 1;
+                    }
+                    _ => break,
+                }
+            }
+            places = places.max(count);
+        }
+        if rest != BigInt::one() {
+            return None;
+        }
+        let scaled = (&self.numer * &BigInt::pow10(places)).div_rem(&self.denom).unwrap().0;
+        let digits = scaled.abs().to_string();
+        // Padded without a format width, which is limited to 65535
+        let digits = "0".repeat((places + 1).saturating_sub(digits.len())) + &digits;
+        let sign = if scaled.is_negative() { "-" } else { "" };
+        Some(match places {
+            0 => format!("{}{}", sign, digits),
+            _ => format!("{}{}.{}", sign, &digits[..digits.len() - places], &digits[digits.len() - places..]),
+        })
+    }
+}
+
+impl Neg for &Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational { numer: -&self.numer, denom: self.denom.clone() }
+    }
+}
+
+impl Add for &Rational {
+    type Output = Rational;
+
+    fn add(self, other: &Rational) -> Rational {
+        Rational::new(&(&self.numer * &other.denom) + &(&other.numer * &self.denom), &self.denom * &other.denom).unwrap()
+    }
+}
+
+impl Sub for &Rational {
+    type Output = Rational;
+
+    fn sub(self, other: &Rational) -> Rational {
+        Rational::new(&(&self.numer * &other.denom) - &(&other.numer * &self.denom), &self.denom * &other.denom).unwrap()
+    }
+}
+
+impl Mul for &Rational {
+    type Output = Rational;
+
+    fn mul(self, other: &Rational) -> Rational {
+        Rational::new(&self.numer * &other.numer, &self.denom * &other.denom).unwrap()
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BigInt, Rational};
+
+    fn rational(value: f64) -> Rational {
+        Rational::from_f64(value).unwrap()
+    }
+
+    fn fraction(numer: f64, denom: f64) -> Rational {
+        rational(numer).checked_div(&rational(denom)).unwrap()
+    }
+
+    #[test]
+    fn rational_arithmetic_is_exact() {
+        assert_eq!((&fraction(1., 3.) + &fraction(1., 6.)).to_string(), "1/2");
+        assert_eq!(&rational(0.1) + &rational(0.2), rational(0.3));
+        assert_eq!(fraction(-2., 4.).to_string(), "-1/2");
+        assert_eq!(fraction(2., 3.).checked_pow(-2).unwrap().to_string(), "9/4");
+        assert!(rational(1.).checked_div(&rational(0.)).is_none());
+    }
+
+    #[test]
+    fn rational_approximates_floats() {
+        assert_eq!(Rational::approximate(1. / 3., 1_000_000), Some(fraction(1., 3.)));
+        assert_eq!(Rational::approximate(-22. / 7., 1_000_000), Some(fraction(-22., 7.)));
+        assert_eq!(Rational::approximate(5., 1_000_000), Some(rational(5.)));
+        assert_eq!(Rational::approximate(2f64.sqrt(), 1_000_000), None);
+    }
+
+    #[test]
+    fn rational_converts_to_decimals() {
+        assert_eq!(rational(-12.375).to_decimal_string(), Some("-12.375".into()));
+        assert_eq!(fraction(1., 40.).to_decimal_string(), Some("0.025".into()));
+        assert_eq!(fraction(1., 3.).to_decimal_string(), None);
+        let tiny = Rational::new(BigInt::one(), BigInt::from(2).pow(65536)).unwrap();
+        assert_eq!(tiny.to_decimal_string().unwrap().len(), 65538);
+        assert_eq!(fraction(1., 3.).to_f64(), 1. / 3.);
+        // Both parts of 3^1000 / 2^1000 are too large for a float, the quotient is not
+        let large = rational(3.).checked_pow(1000).unwrap().checked_div(&rational(2.).checked_pow(1000).unwrap()).unwrap();
+        assert!((large.to_f64() / 1.5f64.powi(1000) - 1.).abs() < 1e-12);
+    }
+}
@@ -9,5 +9,7 @@ pub enum ParseError {
     UnmatchedClosingParen,
     UnmatchedOpeningBracket,
     UnmatchedClosingBracket,
-    WrongBracket
+    WrongBracket,
+    UnknownUnit(String),
+    InvalidUnit,
 }
\ No newline at end of file
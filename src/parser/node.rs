@@ -1,16 +1,21 @@
-use crate::tokenizer::Token;
+use std::fmt;
 
-#[derive(Debug, Clone, PartialEq)]
+use crate::{number::{complex::Complex, matrix::Matrix, rational::Rational}, tokenizer::{Comparison, Logic, Span, Token}, units::{format_number, Quantity, Unit}};
+
+#[derive(Debug, Clone)]
 pub struct ASTNode {
     pub children: Vec<ASTNode>, // Maybe make this option, s.t. there are no vector deallocations on every node drop and no allocations on delimeters
-    pub node_type: ASTNodeType
+    pub node_type: ASTNodeType,
+    /// Position of the node in the source line, if it is known
+    pub span: Option<Span>,
 }
 
 impl ASTNode {
     pub fn delimeter(token: Token) -> Self {
         ASTNode {
             children: vec![],
-            node_type: ASTNodeType::Delimeter(token)
+            node_type: ASTNodeType::Delimeter(token),
+            span: None,
         }
     }
 
@@ -19,25 +24,135 @@ impl ASTNode {
     }
 
     pub fn empty(children: Vec<ASTNode>) -> Self {
-        ASTNode {
-            children,
-            node_type: ASTNodeType::Empty
-        }
+        Self::new(ASTNodeType::Empty, children)
     }
 
+    /// Creates a node spanning over all of its children
     pub fn new(node_type: ASTNodeType, children: Vec<ASTNode>) -> Self {
+        let span = children_span(&children);
         ASTNode {
             children,
-            node_type
+            node_type,
+            span
         }
     }
+
+    pub fn with_span(mut self, span: Option<Span>) -> Self {
+        self.span = span;
+        self
+    }
+}
+
+/// Returns the smallest span covering every child that has a span
+pub fn children_span(children: &[ASTNode]) -> Option<Span> {
+    children
+        .iter()
+        .filter_map(|x| x.span)
+        .reduce(Span::merge)
+}
+
+/// Merges two optional spans, ignoring the missing ones
+pub fn merge_spans(a: Option<Span>, b: Option<Span>) -> Option<Span> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.merge(b)),
+        (a, None) => a,
+        (None, b) => b,
+    }
 }
 
 impl Default for ASTNode {
     fn default() -> Self {
         ASTNode {
             children: vec![],
-            node_type: ASTNodeType::Empty
+            node_type: ASTNodeType::Empty,
+            span: None,
+        }
+    }
+}
+
+// Spans only describe where the node came from, so they are not a part of its structure
+impl PartialEq for ASTNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.node_type == other.node_type && self.children == other.children
+    }
+}
+
+/// How tightly an expression binds, operands binding looser than their operation are parenthesised
+fn precedence(node: &ASTNode) -> u8 {
+    match &node.node_type {
+        ASTNodeType::Equality | ASTNodeType::Comparison(_) | ASTNodeType::Logic(_) | ASTNodeType::Conversion | ASTNodeType::List | ASTNodeType::Assignment => 0,
+        ASTNodeType::Sum | ASTNodeType::Difference | ASTNodeType::Complex(_) => 1,
+        ASTNodeType::Delimeter(Token::Number(num)) if *num < 0. => 2,
+        ASTNodeType::Product | ASTNodeType::Quotient | ASTNodeType::Rational(_) | ASTNodeType::Quantity(_) => 2,
+        ASTNodeType::Power => 3,
+        _ => 4,
+    }
+}
+
+fn starts_with_number(node: &ASTNode) -> bool {
+    match (&node.node_type, node.children.first()) {
+        (ASTNodeType::Delimeter(Token::Number(_)), _) => true,
+        (ASTNodeType::Product | ASTNodeType::Quotient | ASTNodeType::Power, Some(first)) => precedence(first) >= precedence(node) && starts_with_number(first),
+        _ => false,
+    }
+}
+
+/// Operands of logical operations are parenthesised, unless they bind tighter, ex. not a and (b or c)
+fn logic_operand(node: &ASTNode, logic: Logic) -> String {
+    let binding = |logic: Logic| match logic {
+        Logic::Or | Logic::Xor => 0,
+        Logic::And => 1,
+        Logic::Not => 2,
+    };
+    match &node.node_type {
+        ASTNodeType::Logic(inner) if binding(*inner) > binding(logic) => node.to_string(),
+        ASTNodeType::Logic(Logic::Not) if logic == Logic::Not => node.to_string(),
+        ASTNodeType::Logic(_) | ASTNodeType::Comparison(_) | ASTNodeType::Equality if logic == Logic::Not => format!("({})", node),
+        ASTNodeType::Logic(_) => format!("({})", node),
+        _ => node.to_string(),
+    }
+}
+
+/// Writes the expression the way it would be typed, ex. 3 x^2 + 1
+impl fmt::Display for ASTNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = |node: &ASTNode, min: u8| if precedence(node) < min {
+            format!("({})", node)
+        } else {
+            node.to_string()
+        };
+        let join = |children: &[ASTNode]| children.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", ");
+        match (&self.node_type, self.children.as_slice()) {
+            (ASTNodeType::Sum, [a, b]) => write!(f, "{} + {}", operand(a, 1), operand(b, 1)),
+            (ASTNodeType::Difference, [a, b]) => write!(f, "{} - {}", operand(a, 1), operand(b, 2)),
+            (ASTNodeType::Product, [a, b]) => match (&a.node_type, &b.node_type) {
+                (ASTNodeType::Delimeter(Token::Number(num)), _) if *num == -1. => write!(f, "-{}", operand(b, 2)),
+                // A coefficient is written in front without a sign, ex. 2 x, unless two numbers would meet, ex. 2 * 3^x
+                (ASTNodeType::Delimeter(Token::Number(_)), _) if !starts_with_number(b) => write!(f, "{} {}", a, operand(b, 2)),
+                _ => write!(f, "{} * {}", operand(a, 2), operand(b, 2)),
+            },
+            (ASTNodeType::Quotient, [a, b]) => write!(f, "{} / {}", operand(a, 2), operand(b, 3)),
+            (ASTNodeType::Power, [a, b]) => write!(f, "{}^{}", operand(a, 4), operand(b, 4)),
+            (ASTNodeType::Equality, [a, b]) => write!(f, "{} = {}", a, b),
+            (ASTNodeType::Comparison(comparison), [a, b]) => write!(f, "{} {} {}", a, comparison.symbol(), b),
+            (ASTNodeType::Logic(Logic::Not), [a]) => write!(f, "not {}", logic_operand(a, Logic::Not)),
+            (ASTNodeType::Logic(logic), [a, b]) => write!(f, "{} {} {}", logic_operand(a, *logic), logic.name(), logic_operand(b, *logic)),
+            (ASTNodeType::Conversion, [a, b]) => write!(f, "{} to {}", a, b),
+            (ASTNodeType::Assignment, [a]) => write!(f, "let {}", a),
+            (ASTNodeType::Function(name), args) => write!(f, "{}({})", name, join(args)),
+            (ASTNodeType::List, children) => write!(f, "{}", join(children)),
+            (ASTNodeType::Vector, children) => write!(f, "({})", join(children)),
+            (ASTNodeType::Empty, children) => write!(f, "{}", children.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(" ")),
+            (ASTNodeType::Delimeter(Token::Number(num)), _) => write!(f, "{}", format_number(*num)),
+            (ASTNodeType::Delimeter(Token::Decimal(num)), _) => write!(f, "{}", num),
+            (ASTNodeType::Delimeter(Token::Name(name)), _) => write!(f, "{}", name),
+            (ASTNodeType::Delimeter(Token::Boolean(value)), _) => write!(f, "{}", value),
+            (ASTNodeType::Unit(unit), _) => write!(f, "[{}]", unit.name),
+            (ASTNodeType::Quantity(quantity), _) => write!(f, "{}", quantity),
+            (ASTNodeType::Rational(rational), _) => write!(f, "{}", rational),
+            (ASTNodeType::Complex(complex), _) => write!(f, "{}", complex),
+            (ASTNodeType::Matrix(matrix), _) => write!(f, "{}", matrix),
+            _ => write!(f, "{}", self.to_latex()),
         }
     }
 }
@@ -50,11 +165,29 @@ pub enum ASTNodeType {
     Quotient,
     Power,
     Equality,
+    /// Inequality or comparison of two sides, ex. 2 x + 1 < 7
+    Comparison(Comparison),
+    /// Logical operation on booleans, ex. a and not b, `not` has a single child
+    Logic(Logic),
     Delimeter(Token),
     // Error(String),
     Function(String),
     FnArgument(usize),
     List,
+    /// Parenthesised list, ex. (1, 2), a vector literal or a row of a matrix literal
+    Vector,
     Assignment,
+    /// Unit in brackets, ex. [m/s^2]
+    Unit(Unit),
+    /// Value with a dimension, created by the resolver
+    Quantity(Quantity),
+    /// Exact fraction, created by the resolver when it works with rational numbers
+    Rational(Rational),
+    /// Complex number with a nonzero imaginary part, created by the resolver
+    Complex(Complex),
+    /// Matrix or vector, created by the resolver
+    Matrix(Matrix),
+    /// Expressing a value in a different unit, ex. 3 [km] to [mi]
+    Conversion,
     Empty,
 }
@@ -1,29 +1,52 @@
-use crate::{parser::node::ASTNodeType, tokenizer::{Operation, Token}};
+use crate::{parser::node::ASTNodeType, tokenizer::{Comparison, Logic, Operation, SpannedToken, Token}, units};
 
-use super::{*, node::ASTNode, walkers::post_order_mut};
+use super::{*, node::{ASTNode, merge_spans}, walkers::post_order_mut};
 
 static EXP_TOKENS: &[Token] = &[Token::Operation(Operation::Exp)];
 static DIV_TOKENS: &[Token] = &[Token::Operation(Operation::Div)];
 static MUL_TOKENS: &[Token] = &[Token::Operation(Operation::Mul)];
-static ADD_TOKENS: &[Token] = &[Token::Operation(Operation::Add)];
-static SUB_TOKENS: &[Token] = &[Token::Operation(Operation::Sub)];
+static ADD_SUB_TOKENS: &[Token] = &[Token::Operation(Operation::Add), Token::Operation(Operation::Sub)];
+static NOT_TOKENS: &[Token] = &[Token::Logic(Logic::Not)];
+static AND_TOKENS: &[Token] = &[Token::Logic(Logic::And)];
+static OR_XOR_TOKENS: &[Token] = &[Token::Logic(Logic::Or), Token::Logic(Logic::Xor)];
+static COMPARISON_TOKENS: &[Token] = &[
+    Token::Comparison(Comparison::Less),
+    Token::Comparison(Comparison::Greater),
+    Token::Comparison(Comparison::LessEqual),
+    Token::Comparison(Comparison::GreaterEqual),
+    Token::Comparison(Comparison::NotEqual),
+];
+
+pub fn parse(tokens: &[Token]) -> Result<ASTNode, ParseError> {
+    parse_nodes(wrap_tokens(tokens))
+}
+
+/// Parses tokens the same way as `parse`, but carries their spans into the created nodes
+pub fn parse_spanned(tokens: &[SpannedToken]) -> Result<ASTNode, ParseError> {
+    parse_nodes(wrap_spanned_tokens(tokens))
+}
 
-pub fn parse(tokens: &Vec<Token>) -> Result<ASTNode, ParseError> {
-    let mut out = wrap_tokens(tokens);
+fn parse_nodes(mut out: Vec<ASTNode>) -> Result<ASTNode, ParseError> {
 
     // Order of operations:
     //   1. Parens and functions
     //   2. Brackets
     //   3. Negatives
     //   4. Pow
-    //   5. Div
-    //   6. Mul
-    //   7. Add
-    //   8. Sub
+    //   5. Coefficients (2x), implied products (a b) and units bound to them (2 x [m])
+    //   6. Div
+    //   7. Mul
+    //   8. Add and Sub
+    //   9. Unit conversions
+    //  10. Comparisons
+    //  11. Not, and, or and xor
+    //  12. Equalities in function arguments
+    //  13. Lists and vectors
+    //  14. Equality
 
     let mut tree = parse_parens(&mut out)?;
 
-    parse_brackets(&mut tree);
+    parse_brackets(&mut tree)?;
 
     parse_negatives(&mut tree);
 
@@ -40,6 +63,7 @@ pub fn parse(tokens: &Vec<Token>) -> Result<ASTNode, ParseError> {
         &|[name, empty]| {
             if let ASTNodeType::Delimeter(Token::Name(name_string)) = name.node_type {
                 ASTNode::new(ASTNodeType::Function(name_string), empty.children)
+                    .with_span(merge_spans(name.span, empty.span))
             } else {
                 // this is unreachable by the contract of generic_walker
                 unreachable!()
@@ -53,10 +77,10 @@ pub fn parse(tokens: &Vec<Token>) -> Result<ASTNode, ParseError> {
         &DIV_TOKENS,
         &|a, b| {
             match (&a.node_type, &b.node_type) {
-                (ASTNodeType::Delimeter(Token::Number(_)), ASTNodeType::Delimeter(Token::Number(_))) => Some(ASTNode {
-                    node_type: ASTNodeType::Quotient,
-                    children: vec![std::mem::take(a), std::mem::take(b)]
-                }),
+                (ASTNodeType::Delimeter(Token::Number(_) | Token::Decimal(_)), ASTNodeType::Delimeter(Token::Number(_) | Token::Decimal(_))) => Some(ASTNode::new(
+                    ASTNodeType::Quotient,
+                    vec![std::mem::take(a), std::mem::take(b)]
+                )),
                 _ => None
             }
         }
@@ -66,80 +90,104 @@ pub fn parse(tokens: &Vec<Token>) -> Result<ASTNode, ParseError> {
     walkers::interfix_walker(
         &mut tree,
         &EXP_TOKENS,
-        &|a, b| ASTNode {
-            node_type: ASTNodeType::Power,
-            children: vec![a, b]
-        }
+        &|a, b| ASTNode::new(ASTNodeType::Power, vec![a, b])
     );
 
+    parse_coefficients(&mut tree);
+
+    parse_implied_products(&mut tree);
+
     // Division
     walkers::interfix_walker(
         &mut tree,
         &DIV_TOKENS,
-        &|a, b| ASTNode {
-            node_type: ASTNodeType::Quotient,
-            children: vec![a, b]
-        }
+        &|a, b| ASTNode::new(ASTNodeType::Quotient, vec![a, b])
     );
 
     // Multiplication
     walkers::interfix_walker(
         &mut tree,
         &MUL_TOKENS,
-        &|a, b| ASTNode {
-            node_type: ASTNodeType::Product,
-            children: vec![a, b]
+        &|a, b| ASTNode::new(ASTNodeType::Product, vec![a, b])
+    );
+
+    // Addition and subtraction, left to right (10 - 2 + 3 is 11)
+    walkers::operator_interfix_walker(
+        &mut tree,
+        &ADD_SUB_TOKENS,
+        &|operation, a, b| match operation {
+            Token::Operation(Operation::Sub) => ASTNode::new(ASTNodeType::Difference, vec![a, b]),
+            _ => ASTNode::new(ASTNodeType::Sum, vec![a, b]),
         }
     );
 
-    // Addition
+    // Unit conversions
     walkers::interfix_walker(
         &mut tree,
-        &ADD_TOKENS,
-        &|a, b| ASTNode {
-            node_type: ASTNodeType::Sum,
-            children: vec![a, b]
+        &vec![Token::Conversion],
+        &|a, b| ASTNode::new(ASTNodeType::Conversion, vec![a, b])
+    );
+
+    // Comparisons
+    walkers::operator_interfix_walker(
+        &mut tree,
+        &COMPARISON_TOKENS,
+        &|operation, a, b| match operation {
+            Token::Comparison(comparison) => ASTNode::new(ASTNodeType::Comparison(*comparison), vec![a, b]),
+            _ => unreachable!(),
         }
     );
 
-    // Subtraction
+    // Logical operations, not binds tighter than and, which binds tighter than or and xor
+    walkers::prefix_walker(
+        &mut tree,
+        &NOT_TOKENS,
+        &|a| ASTNode::new(ASTNodeType::Logic(Logic::Not), vec![a])
+    );
     walkers::interfix_walker(
         &mut tree,
-        &SUB_TOKENS,
-        &|a, b| ASTNode {
-            node_type: ASTNodeType::Difference,
-            children: vec![a, b]
+        &AND_TOKENS,
+        &|a, b| ASTNode::new(ASTNodeType::Logic(Logic::And), vec![a, b])
+    );
+    walkers::operator_interfix_walker(
+        &mut tree,
+        &OR_XOR_TOKENS,
+        &|operation, a, b| match operation {
+            Token::Logic(logic) => ASTNode::new(ASTNodeType::Logic(*logic), vec![a, b]),
+            _ => unreachable!(),
         }
     );
 
+    // Equalities in the arguments of a call are separated by commas, ex. if(n = 0, 1, 2)
+    post_order_mut(&mut tree, &mut |node| if matches!(node.node_type, ASTNodeType::Function(_)) {
+        walkers::interfix_walker(
+            node,
+            &vec![Token::Equals],
+            &|a, b| ASTNode::new(ASTNodeType::Equality, vec![a, b])
+        );
+    });
+
     // Lists
     walkers::interfix_walker(
         &mut tree,
         &vec![Token::Comma],
-        &|a, b| ASTNode {
-            node_type: ASTNodeType::List,
-            children: vec![a, b]
-        }
+        &|a, b| ASTNode::new(ASTNodeType::List, vec![a, b])
     );
 
+    parse_vectors(&mut tree);
+
     // Equality
     walkers::interfix_walker(
         &mut tree,
         &vec![Token::Equals],
-        &|a, b| ASTNode {
-            node_type: ASTNodeType::Equality,
-            children: vec![a, b]
-        }
+        &|a, b| ASTNode::new(ASTNodeType::Equality, vec![a, b])
     );
 
     // Let bindings
     walkers::prefix_walker(
         &mut tree,
         &vec![Token::Let],
-        &|a, | ASTNode {
-            node_type: ASTNodeType::Assignment,
-            children: vec![a]
-        }
+        &|a, | ASTNode::new(ASTNodeType::Assignment, vec![a])
     );
 
     optimise_tree(&mut tree);
@@ -147,13 +195,20 @@ pub fn parse(tokens: &Vec<Token>) -> Result<ASTNode, ParseError> {
     Ok(tree)
 }
 
-pub fn wrap_tokens(tokens: &Vec<Token>) -> Vec<ASTNode> {
+pub fn wrap_tokens(tokens: &[Token]) -> Vec<ASTNode> {
     tokens
         .iter()
         .map(|x| ASTNode { node_type: ASTNodeType::Delimeter(x.clone()), ..Default::default() })
         .collect()
 }
 
+pub fn wrap_spanned_tokens(tokens: &[SpannedToken]) -> Vec<ASTNode> {
+    tokens
+        .iter()
+        .map(|x| ASTNode::delimeter(x.token.clone()).with_span(Some(x.span)))
+        .collect()
+}
+
 pub fn parse_parens(tokens: &mut Vec<ASTNode>) -> Result<ASTNode, ParseError> {
     // Parens
     // Store the subtree roots in a vec and add them to previous roots on closing parens
@@ -164,20 +219,21 @@ pub fn parse_parens(tokens: &mut Vec<ASTNode>) -> Result<ASTNode, ParseError> {
     for token in tokens {
         match token.node_type {
             ASTNodeType::Delimeter(Token::OpeningParen) => {
-                roots.push(ASTNode::default());
+                roots.push(ASTNode::default().with_span(token.span));
             }
             ASTNodeType::Delimeter(Token::ClosingParen) => {
                 if roots.len() <= 1 {
                     return Err(ParseError::UnmatchedClosingParen);
                 } else {
                     // There are >= 2 elements in roots
-                    let curr_root = roots.pop().unwrap();
+                    let mut curr_root = roots.pop().unwrap();
+                    curr_root.span = merge_spans(curr_root.span, token.span);
 
                     roots.last_mut().unwrap().children.push(curr_root);
                 }
             }
             _ => {
-                if roots.len() >= 1 {
+                if !roots.is_empty() {
                     let stack_top = roots.last_mut().unwrap();
                     // TODO: maybe find a better way?
                     // without creating the default struct every time
@@ -196,8 +252,96 @@ pub fn parse_parens(tokens: &mut Vec<ASTNode>) -> Result<ASTNode, ParseError> {
     // roots[0]
 }
 
-pub fn parse_brackets(_tree: &mut ASTNode) {
-    // println!("parsing brackets");
+/// Binds coefficients to the term right after them (2x, 3x^2, 2(x + 1)), so that they are not split by + or =
+pub fn parse_coefficients(tree: &mut ASTNode) {
+    post_order_mut(tree, &mut |node| {
+        // Only sequences which are not parsed yet, children of operations like Power(2, x) are already in place
+        if !matches!(node.node_type, ASTNodeType::Empty | ASTNodeType::Function(_)) {
+            return;
+        }
+        let mut i = 0;
+        while i + 1 < node.children.len() {
+            let is_coefficient = matches!(node.children[i].node_type, ASTNodeType::Delimeter(Token::Number(_) | Token::Decimal(_)))
+                && matches!(
+                    node.children[i + 1].node_type,
+                    ASTNodeType::Delimeter(Token::Name(_)) | ASTNodeType::Function(_) | ASTNodeType::Power | ASTNodeType::Empty
+                );
+            if is_coefficient {
+                let term = node.children.remove(i + 1);
+                let coefficient = std::mem::take(&mut node.children[i]);
+                node.children[i] = ASTNode::new(ASTNodeType::Product, vec![coefficient, term]);
+            }
+            i += 1;
+        }
+    });
+}
+
+/// Groups operands written next to each other into products, ex. a b or 2 x y [m], before any other operation.
+/// A unit binds to the whole product before it, so that 2 x [m] is (2 x) [m] and not 2 and x [m].
+pub fn parse_implied_products(tree: &mut ASTNode) {
+    let is_operand = |x: &ASTNode| !matches!(&x.node_type, ASTNodeType::Delimeter(token) if !matches!(token, Token::Name(_) | Token::Number(_) | Token::Decimal(_)));
+    post_order_mut(tree, &mut |node| {
+        if !matches!(node.node_type, ASTNodeType::Empty | ASTNodeType::Function(_)) {
+            return;
+        }
+        let mut i = 0;
+        while i + 1 < node.children.len() {
+            let (a, b) = (&node.children[i], &node.children[i + 1]);
+            // Products grouped so far continue with their last factor, ex. 2 x y
+            let mut last = a;
+            while last.node_type == ASTNodeType::Product && last.children.len() == 2 {
+                last = &last.children[1];
+            }
+            let joins = match b.node_type {
+                ASTNodeType::Unit(_) => !matches!(a.node_type, ASTNodeType::Unit(_)),
+                _ => is_implied_multiplication(last, b),
+            };
+            if is_operand(a) && is_operand(b) && joins {
+                let factor = node.children.remove(i + 1);
+                let product = std::mem::take(&mut node.children[i]);
+                node.children[i] = ASTNode::new(ASTNodeType::Product, vec![product, factor]);
+            } else {
+                i += 1;
+            }
+        }
+    });
+}
+
+/// Replaces the contents of brackets with unit nodes
+pub fn parse_brackets(tree: &mut ASTNode) -> Result<(), ParseError> {
+    let mut result = Ok(());
+
+    post_order_mut(tree, &mut |node| {
+        let mut i = 0;
+        while result.is_ok() && i < node.children.len() {
+            match node.children[i].node_type {
+                ASTNodeType::Delimeter(Token::OpeningBracket) => {
+                    let end = node.children[(i + 1)..]
+                        .iter()
+                        .position(|x| matches!(x.node_type, ASTNodeType::Delimeter(Token::OpeningBracket) | ASTNodeType::Delimeter(Token::ClosingBracket)))
+                        .map(|x| x + i + 1);
+                    match end {
+                        Some(end) if node.children[end].node_type == ASTNodeType::Delimeter(Token::ClosingBracket) => {
+                            let span = merge_spans(node.children[i].span, node.children[end].span);
+                            match units::parse_unit(&node.children[(i + 1)..end]) {
+                                Ok(unit) => {
+                                    node.children.splice(i..=end, vec![ASTNode::new(ASTNodeType::Unit(unit), vec![]).with_span(span)]);
+                                }
+                                Err(err) => result = Err(err),
+                            }
+                        }
+                        Some(_) => result = Err(ParseError::WrongBracket), // nested brackets
+                        None => result = Err(ParseError::UnmatchedOpeningBracket),
+                    }
+                }
+                ASTNodeType::Delimeter(Token::ClosingBracket) => result = Err(ParseError::UnmatchedClosingBracket),
+                _ => ()
+            }
+            i += 1;
+        }
+    });
+
+    result
 }
 
 pub fn parse_negatives(tree: &mut ASTNode) {
@@ -208,11 +352,19 @@ pub fn parse_negatives(tree: &mut ASTNode) {
             let mut i = 0;
             while i < node.children.len() - 1 {
                 match &node.children[i].node_type {
-                    ASTNodeType::Delimeter(delimeter) if *delimeter == Token::Operation(Operation::Sub) => {
-                        if i == 0 || matches!(node.children[i - 1].node_type, ASTNodeType::Delimeter(Token::Operation(_))) {
-                            if let ASTNodeType::Delimeter(Token::Number(num)) = node.children[i + 1].node_type {
-                                node.children.splice(i..=(i + 1), vec![ASTNode::number(-num)]);
-                            }
+                    ASTNodeType::Delimeter(delimeter) if *delimeter == Token::Operation(Operation::Sub)
+                        && (i == 0 || matches!(
+                            node.children[i - 1].node_type,
+                            ASTNodeType::Delimeter(Token::Operation(_) | Token::Equals | Token::Comparison(_) | Token::Logic(_) | Token::Comma | Token::Conversion)
+                        )) => {
+                        let negated = match &node.children[i + 1].node_type {
+                            ASTNodeType::Delimeter(Token::Number(num)) => Some(Token::Number(-num)),
+                            ASTNodeType::Delimeter(Token::Decimal(num)) => Some(Token::Decimal(-num)),
+                            _ => None,
+                        };
+                        if let Some(negated) = negated {
+                            let span = merge_spans(node.children[i].span, node.children[i + 1].span);
+                            node.children.splice(i..=(i + 1), vec![ASTNode::delimeter(negated).with_span(span)]);
                         }
                     }
                     _ => ()
@@ -250,6 +402,28 @@ pub fn parse_negatives(tree: &mut ASTNode) {
     // }
 }
 
+/// Parenthesised lists are vector literals, ex. (1, 2), so a list of them is a matrix, ex. ((1, 2), (3, 4))
+pub fn parse_vectors(tree: &mut ASTNode) {
+    post_order_mut(tree, &mut |node| {
+        // Only the children are checked, a list which is not in parens is not a vector
+        for child in &mut node.children {
+            if child.node_type == ASTNodeType::Empty && matches!(child.children.as_slice(), [ASTNode { node_type: ASTNodeType::List, .. }]) {
+                let list = child.children.pop().unwrap();
+                child.node_type = ASTNodeType::Vector;
+                child.children = flatten_list(list);
+            }
+        }
+    });
+}
+
+/// Flattens nested List nodes into their elements
+fn flatten_list(node: ASTNode) -> Vec<ASTNode> {
+    match node.node_type {
+        ASTNodeType::List => node.children.into_iter().flat_map(flatten_list).collect(),
+        _ => vec![node],
+    }
+}
+
 pub fn optimise_tree(tree: &mut ASTNode) {
     // println!("optimising");
     // Collapse empty, where possible
@@ -273,7 +447,7 @@ pub fn collapse_empty(tree: &mut ASTNode) {
                         let removed = node.children
                             .splice(i..=(i + 1), vec![ASTNode::new(ASTNodeType::Product, vec![])])
                             .collect::<Vec<ASTNode>>();
-                        node.children[i].children = removed;
+                        node.children[i] = ASTNode::new(ASTNodeType::Product, removed);
                     }
                     i += 1;
                 }
@@ -291,6 +465,10 @@ pub fn collapse_empty(tree: &mut ASTNode) {
 /// Used when no operator is used or to make generated text less verbose.
 /// ex. 10(x + 3) is the same as 10 * (x + 3)
 pub fn is_implied_multiplication(a: &ASTNode, b: &ASTNode) -> bool {
+    if matches!(b.node_type, ASTNodeType::Unit(_)) {
+        // 10 [m], x [m/s]
+        return !matches!(a.node_type, ASTNodeType::Delimeter(Token::Operation(_)) | ASTNodeType::Unit(_));
+    }
     match a.node_type {
         // TODO: treat quotients the same as numbers only if quotient children are also numbers
         ASTNodeType::Delimeter(Token::Number(_)) // 10(x + 3)
@@ -308,9 +486,10 @@ pub fn is_implied_multiplication(a: &ASTNode, b: &ASTNode) -> bool {
                 },
                 // ASTNodeType::Equality => false, // no
                 ASTNodeType::Delimeter(Token::Name(_)) => true,
-                ASTNodeType::Delimeter(Token::Number(_)) => true,
+                ASTNodeType::Delimeter(Token::Number(_) | Token::Decimal(_)) => true,
                 // ASTNodeType::Function(_) => false, // no
                 ASTNodeType::Empty => true, // yes
+                ASTNodeType::Vector => true, // 2(1, 2)
                 _ => false
             }
             // false
@@ -322,9 +501,12 @@ pub fn is_implied_multiplication(a: &ASTNode, b: &ASTNode) -> bool {
                 | ASTNodeType::Product // yes
                 | ASTNodeType::Quotient // yes
                 | ASTNodeType::Delimeter(Token::Name(_)) => true,
-                // ASTNodeType::Power => todo!(), // no
+                ASTNodeType::Power => match b.children.first().unwrap().node_type { // pi r^2, but not x 2^3
+                    ASTNodeType::Delimeter(Token::Name(_)) | ASTNodeType::Empty => true,
+                    _ => false
+                },
+                ASTNodeType::Function(_) => true, // n f(n - 1)
                 // ASTNodeType::Equality => todo!(), // no
-                // ASTNodeType::Function(_) => todo!(), // no
                 // ASTNodeType::Empty => todo!(), // yes
                 _ => false
             }
@@ -348,11 +530,11 @@ pub fn is_implied_multiplication(a: &ASTNode, b: &ASTNode) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use crate::{parser::{ParseError, node::{ASTNode, ASTNodeType}, parsers::{self, parse}}, tokenizer::{Operation, Token}};
+    use crate::{parser::{ParseError, node::{ASTNode, ASTNodeType}, parsers::{self, parse}}, tokenizer::{Comparison, Logic, Operation, Span, Token, tokenize_spanned}};
 
     #[test]
     fn parse_parens() {
-        let x = parsers::parse_parens(&mut parsers::wrap_tokens(&mut vec![
+        let x = parsers::parse_parens(&mut parsers::wrap_tokens(&[
             Token::OpeningParen,
             Token::OpeningParen,
             Token::OpeningParen,
@@ -371,31 +553,36 @@ mod tests {
                                 ASTNode {
                                     children: vec![],
                                     node_type: ASTNodeType::Empty,
+                                    span: None,
                                 },
                             ],
                             node_type: ASTNodeType::Empty,
+                            span: None,
                         },
                     ],
                     node_type: ASTNodeType::Empty,
+                    span: None,
                 },
                 ASTNode {
                     children: vec![],
                     node_type: ASTNodeType::Empty,
+                    span: None,
                 },
             ],
             node_type: ASTNodeType::Empty,
+            span: None,
         });
     }
     #[test]
     fn parse_parens_and_other() {
-        let x = parsers::parse_parens(&mut parsers::wrap_tokens(&mut vec![
+        let x = parsers::parse_parens(&mut parsers::wrap_tokens(&[
             Token::OpeningParen,
             Token::Name("x".into()),
             Token::Operation(Operation::Add),
             Token::Number(10.0),
             Token::ClosingParen,
             Token::Operation(Operation::Div),
-            Token::Number(3.0)
+            Token::Number(3.0),
         ])).unwrap();
         assert_eq!(x, ASTNode {
             children: vec![
@@ -406,24 +593,26 @@ mod tests {
                         ASTNode::delimeter(Token::Number(10.0)),
                     ],
                     node_type: ASTNodeType::Empty,
+                    span: None,
                 },
                 ASTNode::delimeter(Token::Operation(Operation::Div)),
                 ASTNode::delimeter(Token::Number(3.0)),
             ],
             node_type: ASTNodeType::Empty,
+            span: None,
         });
     }
 
     #[test]
     fn parse_parens_errors() {
-        let x = parsers::parse_parens(&mut parsers::wrap_tokens(&mut vec![
+        let x = parsers::parse_parens(&mut parsers::wrap_tokens(&[
             Token::OpeningParen,
             Token::ClosingParen,
             Token::OpeningParen,
         ])).unwrap_err();
         assert_eq!(x, ParseError::UnmatchedOpeningParen);
 
-        let x = parsers::parse_parens(&mut parsers::wrap_tokens(&mut vec![
+        let x = parsers::parse_parens(&mut parsers::wrap_tokens(&[
             Token::OpeningParen,
             Token::ClosingParen,
             Token::OpeningParen,
@@ -436,7 +625,7 @@ mod tests {
 
     #[test]
     fn parse_negatives() {
-        let mut x = parsers::parse_parens(&mut parsers::wrap_tokens(&mut vec![
+        let mut x = parsers::parse_parens(&mut parsers::wrap_tokens(&[
             Token::OpeningParen,
             Token::Operation(Operation::Sub),
             Token::Number(10.0),
@@ -447,7 +636,7 @@ mod tests {
             Token::Number(3.0),
             Token::Operation(Operation::Exp),
             Token::Operation(Operation::Sub),
-            Token::Number(10.0)
+            Token::Number(10.0),
         ])).unwrap();
 
         parsers::parse_negatives(&mut x);
@@ -461,6 +650,7 @@ mod tests {
                         ASTNode::delimeter(Token::Number(7.0)),
                     ],
                     node_type: ASTNodeType::Empty,
+                    span: None,
                 },
                 ASTNode::delimeter(Token::Operation(Operation::Sub)),
                 ASTNode::delimeter(Token::Number(3.0)),
@@ -468,13 +658,14 @@ mod tests {
                 ASTNode::delimeter(Token::Number(-10.0)),
             ],
             node_type: ASTNodeType::Empty,
+            span: None,
         });
     }
 
     // full parse tests
     #[test]
     fn parse_full_square() {
-        let x = parsers::parse(&vec![
+        let x = parsers::parse(&[
             Token::Number(10.),
             Token::Operation(Operation::Exp),
             Token::Number(2.),
@@ -490,9 +681,7 @@ mod tests {
     }
     #[test]
     fn parse_full_number() {
-        let x = parsers::parse(&vec![
-            Token::Number(10.),
-        ]).unwrap();
+        let x = parsers::parse(&[Token::Number(10.)]).unwrap();
         // println!("{:#?}", &x);
         assert_eq!(
             x,
@@ -501,7 +690,7 @@ mod tests {
     }
     #[test]
     fn parse_full_div() {
-        let x = parsers::parse(&vec![
+        let x = parsers::parse(&[
             Token::Number(10.),
             Token::Operation(Operation::Div),
             Token::Number(0.),
@@ -517,7 +706,7 @@ mod tests {
     }
     #[test]
     fn parse_full_sub() {
-        let x = parsers::parse(&vec![
+        let x = parsers::parse(&[
             Token::Number(0.005),
             Token::Operation(Operation::Sub),
             Token::Number(0.002),
@@ -533,7 +722,7 @@ mod tests {
     }
     #[test]
     fn parse_full_negative() {
-        let x = parsers::parse(&vec![
+        let x = parsers::parse(&[
             // Token::Number(0.005),
             Token::Operation(Operation::Sub),
             Token::Number(0.002),
@@ -547,7 +736,7 @@ mod tests {
 
     #[test]
     fn parse_full_fn() {
-        let x = parsers::parse(&vec![
+        let x = parsers::parse(&[
             // Token::Number(0.005),
             Token::Name("my_fun".into()),
             Token::OpeningParen,
@@ -561,7 +750,7 @@ mod tests {
 
     #[test]
     fn parse_full_fn_args() {
-        let x = parsers::parse(&vec![
+        let x = parsers::parse(&[
             // Token::Number(0.005),
             Token::Name("my_fun".into()),
             Token::OpeningParen,
@@ -583,7 +772,7 @@ mod tests {
 
     #[test]
     fn parse_full_fn_args_nesting() {
-        let x = parsers::parse(&vec![
+        let x = parsers::parse(&[
             // Token::Number(0.005),
             Token::Name("my_fun".into()),
             Token::OpeningParen,
@@ -613,7 +802,7 @@ mod tests {
 
     #[test]
     fn is_implied_multiplication() {
-        let x = parsers::parse(&vec![
+        let x = parsers::parse(&[
             Token::Number(10.),
             Token::OpeningParen,
             Token::Number(2.),
@@ -635,7 +824,7 @@ mod tests {
 
     #[test]
     fn is_implied_multiplication_number() {
-        let x = parsers::parse(&vec![
+        let x = parsers::parse(&[
             Token::Number(10.),
             Token::OpeningParen,
             Token::Number(2.),
@@ -652,7 +841,7 @@ mod tests {
 
     #[test]
     fn is_implied_multiplication_quotient() {
-        let x = parsers::parse(&vec![
+        let x = parsers::parse(&[
             Token::Number(1.),
             Token::Operation(Operation::Div),
             Token::Number(2.),
@@ -678,7 +867,7 @@ mod tests {
     }
     #[test]
     fn is_implied_multiplication_num_name() {
-        let x = parsers::parse(&vec![
+        let x = parsers::parse(&[
             Token::Number(10.),
             Token::Name("x".into()),
         ]).unwrap();
@@ -691,10 +880,108 @@ mod tests {
         );
     }
 
+    #[test]
+    fn is_implied_multiplication_name_power() {
+        let x = parsers::parse(&[
+            Token::Name("pi".into()),
+            Token::Name("r".into()),
+            Token::Operation(Operation::Exp),
+            Token::Number(2.),
+        ]).unwrap();
+        assert_eq!(
+            x,
+            ASTNode::new(ASTNodeType::Product, vec![
+                ASTNode::delimeter(Token::Name("pi".into())),
+                ASTNode::new(ASTNodeType::Power, vec![
+                    ASTNode::delimeter(Token::Name("r".into())),
+                    ASTNode::delimeter(Token::Number(2.)),
+                ])
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_spanned_carries_spans() {
+        let x = parsers::parse_spanned(&tokenize_spanned("2 * f(x - 1)").unwrap()).unwrap();
+
+        assert_eq!(x.span, Some(Span::new(0, 12)));
+        assert_eq!(x.children[0].span, Some(Span::new(0, 1)));
+        assert_eq!(x.children[1].span, Some(Span::new(4, 12)));
+        assert_eq!(x.children[1].children[0].span, Some(Span::new(6, 11)));
+    }
+
+    #[test]
+    fn parse_vectors_in_parens() {
+        let number = |x: f64| ASTNode::delimeter(Token::Number(x));
+        let vector = |children: Vec<ASTNode>| ASTNode::new(ASTNodeType::Vector, children);
+        let x = parsers::parse_spanned(&tokenize_spanned("((1, 2), (3, 4))").unwrap()).unwrap();
+        assert_eq!(x, vector(vec![vector(vec![number(1.), number(2.)]), vector(vec![number(3.), number(4.)])]));
+
+        // Function arguments and lists without parens are not vectors
+        let x = parsers::parse_spanned(&tokenize_spanned("f(1, 2)").unwrap()).unwrap();
+        assert_eq!(x.children[0].node_type, ASTNodeType::List);
+        let x = parsers::parse_spanned(&tokenize_spanned("1, 2").unwrap()).unwrap();
+        assert_eq!(x.node_type, ASTNodeType::List);
+    }
+
+    #[test]
+    fn parse_comparisons_before_lists() {
+        let x = parsers::parse_spanned(&tokenize_spanned("if(x <= -1, 2 x + 1 > 3, 0)").unwrap()).unwrap();
+        let args = &x.children[0];
+        assert_eq!(args.node_type, ASTNodeType::List);
+        assert_eq!(args.children[0].children[0].node_type, ASTNodeType::Comparison(Comparison::LessEqual));
+        assert_eq!(args.children[0].children[0].children[1], ASTNode::delimeter(Token::Number(-1.)));
+        assert_eq!(args.children[0].children[1].node_type, ASTNodeType::Comparison(Comparison::Greater));
+        assert_eq!(args.children[0].children[1].children[0].node_type, ASTNodeType::Sum);
+    }
+
+    #[test]
+    fn parse_logic_precedence() {
+        let x = parsers::parse_spanned(&tokenize_spanned("not a or b and x > 1").unwrap()).unwrap();
+        assert_eq!(x.node_type, ASTNodeType::Logic(Logic::Or));
+        assert_eq!(x.children[0].node_type, ASTNodeType::Logic(Logic::Not));
+        assert_eq!(x.children[1].node_type, ASTNodeType::Logic(Logic::And));
+        assert_eq!(x.children[1].children[1].node_type, ASTNodeType::Comparison(Comparison::Greater));
+        assert_eq!(x.to_string(), "not a or b and x > 1");
+
+        let x = parsers::parse_spanned(&tokenize_spanned("not not a and b").unwrap()).unwrap();
+        assert_eq!(x.node_type, ASTNodeType::Logic(Logic::And));
+        assert_eq!(x.children[0].node_type, ASTNodeType::Logic(Logic::Not));
+        assert_eq!(x.children[0].children[0].node_type, ASTNodeType::Logic(Logic::Not));
+        assert_eq!(x.to_string(), "not not a and b");
+    }
+
+    #[test]
+    fn parse_units_bind_to_implied_products() {
+        let x = parsers::parse_spanned(&tokenize_spanned("abs(2 x [m])").unwrap()).unwrap();
+        assert_eq!(x.children.len(), 1);
+        let product = &x.children[0];
+        assert_eq!(product.node_type, ASTNodeType::Product);
+        assert_eq!(product.children[0].node_type, ASTNodeType::Product);
+        assert!(matches!(product.children[1].node_type, ASTNodeType::Unit(_)));
+
+        let x = parsers::parse_spanned(&tokenize_spanned("y [m] + 2 y [m]").unwrap()).unwrap();
+        assert_eq!(x.node_type, ASTNodeType::Sum);
+        assert_eq!(x.to_string(), "y * [m] + 2 y * [m]");
+    }
+
+    #[test]
+    fn parse_equalities_in_arguments() {
+        let x = parsers::parse_spanned(&tokenize_spanned("if(n = 0, 1, 2)").unwrap()).unwrap();
+        let args = &x.children[0];
+        assert_eq!(args.node_type, ASTNodeType::List);
+        assert_eq!(args.children[0].node_type, ASTNodeType::List);
+        assert_eq!(args.children[0].children[0].node_type, ASTNodeType::Equality);
+
+        let x = parsers::parse_spanned(&tokenize_spanned("x = 1, 2").unwrap()).unwrap();
+        assert_eq!(x.node_type, ASTNodeType::Equality);
+        assert_eq!(x.children[1].node_type, ASTNodeType::List);
+    }
+
     #[test]
     fn parse_empty_returns_empty() {
         assert_eq!(
-            parse(&vec![]),
+            parse(&[]),
             Ok(ASTNode::empty(vec![]))
         );
     }
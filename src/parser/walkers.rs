@@ -5,8 +5,8 @@ use crate::tokenizer::Token;
 
 use super::node::{ASTNode, ASTNodeType};
 
-/// Walks over every element in the tree, pre-order, calling modify. Stops on delimeters.
-/// Modifies elements from top to bottom.
+// Walks over every element in the tree, pre-order, calling modify. Stops on delimeters.
+// Modifies elements from top to bottom.
 // pub fn pre_order<F>(tree: &mut ASTNode, modify: &F)
 //     where F : Fn(&mut ASTNode) {
 //     // iterate recursively over everything, stopping on delimeters
@@ -53,11 +53,29 @@ pub fn post_order_mut<F>(tree: &mut ASTNode, modify: &mut F)
     modify(tree);
 }
 
+/// Walks over the tree like post_order_mut, but does not enter the children of nodes for which descend is false.
+pub fn post_order_mut_pruned<F, D>(tree: &mut ASTNode, descend: &D, modify: &mut F)
+    where F : FnMut(&mut ASTNode), D : Fn(&ASTNode) -> bool {
+    if !matches!(tree.node_type, ASTNodeType::Delimeter(_)) && descend(tree) {
+        for child in &mut tree.children {
+            post_order_mut_pruned(child, descend, modify);
+        }
+    }
+    modify(tree);
+}
+
 // Interfix walker reimplemented with standard node traversal functions
 // TODO: Interfix walker as a special case for a generic walker
 /// Walks over a tree and folds expressions of form (* interfix *)
 pub fn interfix_walker<F, T>(tree: &mut ASTNode, interfix_list: &T, create: &F)
     where F : Fn(ASTNode, ASTNode) -> ASTNode, T: Deref<Target = [Token]> {
+    operator_interfix_walker(tree, interfix_list, &|_, a, b| create(a, b));
+}
+
+/// Walks over a tree and folds expressions of form (* interfix *) from left to right,
+/// passing the interfix to `create`, so that operators of the same precedence can be folded together
+pub fn operator_interfix_walker<F, T>(tree: &mut ASTNode, interfix_list: &T, create: &F)
+    where F : Fn(&Token, ASTNode, ASTNode) -> ASTNode, T: Deref<Target = [Token]> {
     post_order_mut(tree, &mut |node| {
         // c-like for loop
         if node.children.len() >= 3 {
@@ -66,6 +84,7 @@ pub fn interfix_walker<F, T>(tree: &mut ASTNode, interfix_list: &T, create: &F)
                 match &node.children[i].node_type {
                     ASTNodeType::Delimeter(delimeter) if interfix_list.contains(delimeter) => {
                         let new_token = create(
+                            &delimeter.clone(),
                             std::mem::take(&mut node.children[i - 1]),
                             std::mem::take(&mut node.children[i + 1])
                         );
@@ -115,7 +134,7 @@ pub fn failing_interfix_walker<F, T>(tree: &mut ASTNode, interfix_list: &T, crea
     });
 }
 
-/// Walks over a tree and folds expressions of form (* interfix *)
+// Walks over a tree and folds expressions of form (* interfix *)
 // pub fn interfix_walker<F, T>(tree: &mut ASTNode, interfix_list: &T, create: &F)
 //     where F : Fn(ASTNode, ASTNode) -> ASTNode, T: Deref<Target = [Token]> {
 //     // need to iterate over all children and recursively walk nested parens
@@ -189,17 +208,19 @@ pub fn failing_interfix_walker<F, T>(tree: &mut ASTNode, interfix_list: &T, crea
 // }
 
 /// Walks over a tree and folds expressions of form (prefix *)
+///
+/// Prefixes are folded right to left, so that a repeated prefix wraps the one after it, ex. `not not a`
 pub fn prefix_walker<F, T>(tree: &mut ASTNode, prefix_list: &T, create: &F)
     where F : Fn(ASTNode) -> ASTNode, T: Deref<Target = [Token]> {
     post_order_mut(tree, &mut |node| {
-        // c-like for loop, because node.children.len() changes
+        // reversed c-like for loop, because node.children.len() changes
         if node.children.len() >= 2 {
-            let mut i = 0;
-            while i < node.children.len() - 1 {
+            let mut i = node.children.len() - 1;
+            while i > 0 {
+                i -= 1;
                 match &node.children[i].node_type {
                     ASTNodeType::Delimeter(delimeter) if prefix_list.contains(delimeter) => {
                         let new_token = create(
-                            // std::mem::take(&mut node.children[i - 1]),
                             std::mem::take(&mut node.children[i + 1])
                         );
 
@@ -207,7 +228,6 @@ pub fn prefix_walker<F, T>(tree: &mut ASTNode, prefix_list: &T, create: &F)
                     }
                     _ => ()
                 }
-                i += 1;
             }
         }
     });
@@ -245,7 +265,7 @@ mod tests {
     fn interfix_walker_div_sum() {
         // simple test
         let mut x = ASTNode {
-            children: parsers::wrap_tokens(&mut vec![
+            children: parsers::wrap_tokens(&[
                 Token::Number(10.),
                 Token::Operation(Operation::Div),
                 Token::Number(2.),
@@ -260,7 +280,8 @@ mod tests {
             &vec![Token::Operation(Operation::Div)],
             &|a, b| ASTNode {
                 node_type: ASTNodeType::Quotient,
-                children: vec![a, b]
+                children: vec![a, b],
+                span: None,
             }
         );
 
@@ -269,10 +290,11 @@ mod tests {
             vec![
                 ASTNode {
                     node_type: ASTNodeType::Quotient,
-                    children: parsers::wrap_tokens(&vec![
+                    children: parsers::wrap_tokens(&[
                         Token::Number(10.),
                         Token::Number(2.),
-                    ])
+                    ]),
+                    span: None,
                 },
                 ASTNode::delimeter(Token::Operation(Operation::Add)),
                 ASTNode::delimeter(Token::Number(7.))
@@ -300,7 +322,8 @@ mod tests {
             &vec![Token::Operation(Operation::Div)],
             &|a, b| ASTNode {
                 node_type: ASTNodeType::Quotient,
-                children: vec![a, b]
+                children: vec![a, b],
+                span: None,
             }
         );
 
@@ -341,7 +364,8 @@ mod tests {
                 matches!(table[1].node_type, ASTNodeType::Delimeter(Token::Operation(Operation::Div))),
             &|[a, _, c]| ASTNode {
                 node_type: ASTNodeType::Quotient,
-                children: vec![a, c]
+                children: vec![a, c],
+                span: None,
             }
         );
 
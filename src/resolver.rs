@@ -1,44 +1,436 @@
 pub mod resolve_message;
 pub mod namespace;
+pub mod builtins;
+pub mod constants;
+pub mod solver;
+pub mod trace;
+pub mod simplify;
+pub mod derivative;
 
-use std::collections::{HashMap, HashSet};
+use std::{cell::Cell, collections::{HashMap, HashSet}};
 
-use crate::{parser::{node::{ASTNode, ASTNodeType}, walkers::{self, post_order_mut}}, tokenizer::Token};
+use crate::{number::{complex::Complex, decimal::Decimal, matrix::Matrix, rational::Rational}, parser::{node::{ASTNode, ASTNodeType}, walkers::{self, post_order, post_order_mut}}, tokenizer::{Comparison, Logic, NumberLiterals, Token}, units::{format_number, Quantity, Unit}};
 
-use namespace::NamespaceElement;
-use resolve_message::ResolveMessage;
+use namespace::{NamespaceElement, UserFunction};
+use resolve_message::{ResolveMessage, ResolveMessageType};
+use solver::{Interval, Solution, SolveError, SystemSolution};
+use trace::SolutionTrace;
+use simplify::simplify;
+use derivative::differentiate;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Resolver {
     pub namespace: HashMap<String, NamespaceElement>,
+    /// Names in the namespace which cannot be reassigned
+    pub constants: HashSet<String>,
+    pub root_policy: RootPolicy,
+    pub number_mode: NumberMode,
+    /// Explains how equations are solved, with an info message for every step
+    pub trace: bool,
+    /// Steps of the last equation solved by isolating the unknown or collecting its terms, only recorded with `trace`
+    last_trace: Option<SolutionTrace>,
+    /// Equations waiting for more lines to be solved together
+    system: EquationSystem,
+    /// Largest depth of nested user function calls, deeper recursion is an error
+    pub recursion_limit: usize,
+    /// Depth of the user function calls being resolved
+    call_depth: Cell<usize>,
+    /// How close values have to be to be equal, ex. 0.1 + 0.2 = 0.3
+    pub tolerance: Tolerance,
 }
 
+/// Consecutive equations with several unknowns, waiting to be solved together
+#[derive(Clone, Debug, Default, PartialEq)]
+struct EquationSystem {
+    lines: Vec<SystemLine>,
+    /// Unknowns of all lines in order of appearance
+    unknowns: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct SystemLine {
+    line_num: usize,
+    /// The line as it was parsed
+    node: ASTNode,
+    /// The line with known names substituted
+    equation: ASTNode,
+    unknowns: Vec<String>,
+}
+
+impl EquationSystem {
+    fn push(&mut self, line: SystemLine) {
+        for name in &line.unknowns {
+            if !self.unknowns.contains(name) {
+                self.unknowns.push(name.clone());
+            }
+        }
+        self.lines.push(line);
+    }
+}
+
+/// Which root is assigned to the unknown, when an equation has more than one solution
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RootPolicy {
+    /// Only assign the unknown if the solution is unique
+    #[default]
+    Unique,
+    Smallest,
+    Largest,
+}
+
+/// How numbers are represented while resolving
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NumberMode {
+    /// Floating point numbers, ex. 0.1 + 0.2 is 0.30000000000000004
+    #[default]
+    Float,
+    /// Exact fractions of arbitrary size, as long as the operations are rational.
+    /// Irrational operations, like sqrt or powers with fractional exponents, fall back to floats.
+    Rational(RationalFormat),
+    /// Decimals of arbitrary size, so that sums of money do not drift.
+    /// Sums and products are exact, divisions are rounded to `precision` significant digits.
+    /// Units are refused, as quantities are calculated with floats.
+    Decimal { precision: usize },
+}
+
+impl NumberMode {
+    /// Exact modes read number literals with all of their digits
+    pub fn literals(&self) -> NumberLiterals {
+        match self {
+            NumberMode::Float => NumberLiterals::Float,
+            NumberMode::Rational(_) | NumberMode::Decimal { .. } => NumberLiterals::Decimal,
+        }
+    }
+}
+
+/// How exact results are printed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RationalFormat {
+    /// 1/3 + 1/6 is 1/2
+    Fraction,
+    /// 1/3 + 1/6 is 0.5, fractions with a non-terminating expansion are rounded
+    Decimal,
+}
+
+/// Values are equal if they differ by at most `absolute`, or by at most `relative` times the larger of them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    pub relative: f64,
+    pub absolute: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance { relative: 1e-9, absolute: 1e-12 }
+    }
+}
+
+impl Tolerance {
+    pub fn new(relative: f64, absolute: f64) -> Self {
+        Tolerance { relative, absolute }
+    }
+
+    pub fn equal(&self, a: f64, b: f64) -> bool {
+        let difference = (a - b).abs();
+        difference <= self.absolute || difference <= self.relative * a.abs().max(b.abs())
+    }
+}
+
+/// Significant digits kept by divisions in decimal mode, unless specified otherwise
+pub const DEFAULT_DECIMAL_PRECISION: usize = 28;
+
+/// Largest number of digits of an exact power, larger powers are calculated with floats
+const MAX_EXACT_POWER_DIGITS: usize = 10_000;
+
+/// Largest denominator of a solution recovered as a fraction in exact mode
+const MAX_SOLUTION_DENOMINATOR: i64 = 1_000_000;
+
+/// Nested user function calls allowed unless specified otherwise
+pub const DEFAULT_RECURSION_LIMIT: usize = 200;
+
+/// Largest recursion limit, every nested call takes around 10 KiB of the stack in debug builds,
+/// so deeper recursion would overflow the 8 MiB stack of the main thread
+pub const MAX_RECURSION_LIMIT: usize = 500;
+
+/// Sums and products with more terms are refused, as they would take too long to evaluate
+const MAX_SERIES_TERMS: f64 = 1e6;
+
 // enum OperationMode {
 //     Equation, Assignment, Expression
 // }
 
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Resolver {
+    /// Creates a resolver with the mathematical constants (pi, e, tau) defined
     pub fn new() -> Self {
-        Self {
+        let mut resolver = Self {
             namespace: HashMap::new(),
+            constants: HashSet::new(),
+            root_policy: RootPolicy::default(),
+            number_mode: NumberMode::default(),
+            trace: false,
+            last_trace: None,
+            system: EquationSystem::default(),
+            recursion_limit: DEFAULT_RECURSION_LIMIT,
+            call_depth: Cell::new(0),
+            tolerance: Tolerance::default(),
+        };
+        for (name, value) in constants::math_constants() {
+            resolver.define_constant(name, value);
+        }
+        // The imaginary unit is not a quantity, so it is defined separately
+        resolver.namespace.insert("i".to_string(), NamespaceElement::Complex(Complex::I));
+        resolver.constants.insert("i".to_string());
+        resolver
+    }
+
+    /// Additionally defines physical constants (G, c, h, k_B, N_A) with their units
+    pub fn with_physics_constants(mut self) -> Self {
+        for (name, value) in constants::physics_constants() {
+            self.define_constant(name, value);
         }
+        self
     }
 
+    /// Uses the given representation of numbers
+    pub fn with_number_mode(mut self, number_mode: NumberMode) -> Self {
+        self.number_mode = number_mode;
+        self
+    }
+
+    /// Explains the steps of solving equations
+    pub fn with_trace(mut self) -> Self {
+        self.trace = true;
+        self
+    }
+
+    /// Limits the depth of nested user function calls, ex. of a recursive function.
+    /// The limit is at most `MAX_RECURSION_LIMIT`, a thread with a smaller stack than 8 MiB needs a lower limit.
+    pub fn with_recursion_limit(mut self, limit: usize) -> Self {
+        self.recursion_limit = limit.min(MAX_RECURSION_LIMIT);
+        self
+    }
+
+    /// Compares values in equality checks with the given tolerance
+    pub fn with_tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Steps of the last equation which was solved by isolating the unknown or collecting its terms, if `trace` is enabled
+    pub fn last_trace(&self) -> Option<&SolutionTrace> {
+        self.last_trace.as_ref()
+    }
+
+    /// Inserts a read-only value into the namespace
+    pub fn define_constant(&mut self, name: &str, value: Quantity) {
+        self.namespace.insert(name.to_string(), NamespaceElement::from_quantity(value));
+        self.constants.insert(name.to_string());
+    }
+
+    fn reassigned_constant(&self, root: &ASTNode) -> Option<ResolveMessage> {
+        let target = match &root.node_type {
+            ASTNodeType::Equality => &root.children[0],
+            ASTNodeType::Assignment if root.children[0].node_type == ASTNodeType::Equality => &root.children[0].children[0],
+            _ => return None,
+        };
+        let name = match &target.node_type {
+            ASTNodeType::Delimeter(Token::Name(name)) | ASTNodeType::Function(name) => name,
+            _ => return None,
+        };
+        if self.constants.contains(name) {
+            Some(ResolveMessage::error(&format!("Cannot reassign constant {}", name)).with_span(target.span))
+        } else {
+            None
+        }
+    }
+
+    /// Units in decimal mode, where quantities would be calculated with floats and lose the digits of the decimals
+    fn units_in_decimal_mode(&self, root: &ASTNode) -> Option<ResolveMessage> {
+        if !matches!(self.number_mode, NumberMode::Decimal { .. }) {
+            return None;
+        }
+        let mut span = None;
+        post_order(root, &mut |x| {
+            let has_unit = match &x.node_type {
+                ASTNodeType::Unit(_) => true,
+                ASTNodeType::Delimeter(Token::Name(name)) => matches!(self.namespace.get(name), Some(NamespaceElement::Quantity(x)) if !x.dimension.is_dimensionless()),
+                _ => false,
+            };
+            if has_unit && span.is_none() {
+                span = Some(x.span);
+            }
+        });
+        span.map(|span| ResolveMessage::error("Units cannot be used in decimal mode, quantities are calculated with floats").with_span(span))
+    }
+
+    /// A call to a function of one parameter with arguments separated by commas, ex. p(1, 2).
+    /// Only a list value, ex. p(xs) with xs = 1, 2, is passed to the parameter as a whole.
+    fn listed_arguments(&self, root: &ASTNode) -> Option<ResolveMessage> {
+        // The body of a function declaration can call the function itself
+        let (declared, body) = match &root.node_type {
+            ASTNodeType::Assignment if root.children[0].node_type == ASTNodeType::Equality => {
+                let (declaration, body) = (&root.children[0].children[0], &root.children[0].children[1]);
+                let declared = match (&declaration.node_type, declaration.children.first()) {
+                    (ASTNodeType::Function(name), Some(args)) => process_fn_args(args).ok().map(|params| (name.clone(), params)),
+                    _ => None
+                };
+                (declared, body)
+            }
+            _ => (None, root)
+        };
+        let mut message = None;
+        post_order(body, &mut |x| {
+            let (name, args) = match (&x.node_type, x.children.first()) {
+                (ASTNodeType::Function(name), Some(args)) if args.node_type == ASTNodeType::List => (name, args),
+                _ => return
+            };
+            let signature = match (self.namespace.get(name), &declared) {
+                (Some(NamespaceElement::Function(function)), _) if function.arity() == 1 => function.signature(name),
+                (None, Some((declared, params))) if declared == name && params.len() == 1 => format!("{}({})", name, params[0]),
+                _ => return
+            };
+            let expected = builtins::Arity::Exact(1).describe();
+            message.get_or_insert(ResolveMessage::error(&format!("{} expects {}, got {}", signature, expected, list_node_to_vec(args).len())).with_span(x.span));
+        });
+        message
+    }
+
+    /// Resolves lines in order. Consecutive equations sharing unknowns are collected into a system
+    /// and solved together once there are as many equations as unknowns.
     pub fn resolve(&mut self, nodes: Vec<(usize, ASTNode)>) -> Vec<(usize, ResolveMessage)> {
         let mut out = Vec::new();
-        // let namespace: Namespace = HashMap::new();
-
-        // How many unknowns are in the expression, useful for determining whether we are able to solve it
-        // multiple occurances count as different unknowns for now, as we can't solve them yet
 
         // node is the root node of a line
         for (line_num, node) in nodes {
-            out.append(&mut self.resolve_line(node).into_iter().map(|x| (line_num, x)).collect());
+            out.append(&mut self.resolve_next(line_num, node));
         }
+        out.append(&mut self.finish());
 
         out
     }
 
+    /// Resolves the next line. Messages of earlier lines can be returned as well,
+    /// when the line completes or ends a system of equations.
+    pub fn resolve_next(&mut self, line_num: usize, node: ASTNode) -> Vec<(usize, ResolveMessage)> {
+        let mut out = Vec::new();
+        if let Some((equation, unknowns)) = self.equation_unknowns(&node) {
+            let shares_unknowns = unknowns.iter().any(|x| self.system.unknowns.contains(x));
+            if unknowns.len() > 1 || shares_unknowns {
+                if !shares_unknowns {
+                    out.append(&mut self.finish());
+                }
+                self.system.push(SystemLine { line_num, node, equation, unknowns });
+                if self.system.lines.len() == self.system.unknowns.len() {
+                    let system = std::mem::take(&mut self.system);
+                    out.append(&mut self.solve_system(system));
+                }
+                return out;
+            }
+        }
+        out.append(&mut self.finish());
+        out.append(&mut self.resolve_line(node).into_iter().map(|x| (line_num, x)).collect());
+        out
+    }
+
+    /// Resolves the lines of a system which did not get enough equations one by one
+    pub fn finish(&mut self) -> Vec<(usize, ResolveMessage)> {
+        std::mem::take(&mut self.system).lines.into_iter()
+            .flat_map(|SystemLine { line_num, node, .. }| self.resolve_line(node).into_iter().map(move |x| (line_num, x)))
+            .collect()
+    }
+
+    /// Whether there are equations waiting for more lines to be solved together
+    pub fn has_pending_system(&self) -> bool {
+        !self.system.lines.is_empty()
+    }
+
+    /// Makes number literals exact in the exact and decimal modes, results of operations on floats stay floats
+    fn exact_literals(&self, root: &mut ASTNode) {
+        let number_mode = self.number_mode;
+        post_order_mut(root, &mut |x| {
+            let exact = match (&x.node_type, number_mode) {
+                (ASTNodeType::Delimeter(Token::Number(num)), NumberMode::Rational(_)) => Rational::from_f64(*num).map(ASTNodeType::Rational),
+                (ASTNodeType::Delimeter(Token::Decimal(num)), NumberMode::Rational(_)) => Rational::from_decimal_str(&num.to_string()).map(ASTNodeType::Rational),
+                (ASTNodeType::Delimeter(Token::Number(num)), NumberMode::Decimal { .. }) => Decimal::from_f64(*num).map(|x| ASTNodeType::Delimeter(Token::Decimal(x))),
+                _ => None,
+            };
+            if let Some(node_type) = exact {
+                *x = ASTNode::new(node_type, vec![]).with_span(x.span);
+            }
+        });
+    }
+
+    /// Returns an equation with known names substituted and its unknowns in order of appearance
+    fn equation_unknowns(&self, node: &ASTNode) -> Option<(ASTNode, Vec<String>)> {
+        if node.node_type != ASTNodeType::Equality {
+            return None;
+        }
+        let mut equation = node.clone();
+        let mut has_empty = false;
+        post_order(&equation, &mut |x| has_empty |= x.node_type == ASTNodeType::Empty);
+        if has_empty {
+            return None;
+        }
+        self.exact_literals(&mut equation);
+        self.resolve_expression(&mut equation).ok()?;
+
+        let mut unknowns: Vec<String> = vec![];
+        post_order(&equation, &mut |x| {
+            if let ASTNodeType::Delimeter(Token::Name(name)) = &x.node_type {
+                if !unknowns.contains(name) {
+                    unknowns.push(name.clone());
+                }
+            }
+        });
+        Some((equation, unknowns))
+    }
+
+    /// Solves a complete system of equations, reporting the values of the unknowns on every line they appear in
+    fn solve_system(&mut self, system: EquationSystem) -> Vec<(usize, ResolveMessage)> {
+        let equations: Vec<_> = system.lines.iter().map(|x| (&x.equation.children[0], &x.equation.children[1])).collect();
+        let report = |lines: &[SystemLine], message: ResolveMessage| lines.iter().map(|x| (x.line_num, message.clone())).collect();
+
+        match solver::solve_linear_system(&equations, &system.unknowns) {
+            Ok(SystemSolution::Unique(values)) => {
+                let equations: Vec<&ASTNode> = system.lines.iter().map(|x| &x.equation).collect();
+                let solutions: Vec<(&str, Quantity)> = system.unknowns.iter().map(|x| x.as_str()).zip(values.iter().copied()).collect();
+                let exact = self.exact_solutions(&equations, &solutions);
+                let exact: Vec<Option<Rational>> = match exact {
+                    Some(exact) => exact.into_iter().map(Some).collect(),
+                    None => vec![None; values.len()],
+                };
+                for ((name, value), exact) in system.unknowns.iter().zip(&values).zip(&exact) {
+                    self.namespace.insert(name.clone(), solution_element(*value, exact));
+                }
+                system.lines.iter()
+                    .map(|line| {
+                        let solutions: Vec<String> = line.unknowns.iter()
+                            .map(|name| {
+                                let index = system.unknowns.iter().position(|x| x == name).unwrap();
+                                format!("{} = {}", name, self.format_solution(values[index], &exact[index], &None))
+                            })
+                            .collect();
+                        (line.line_num, ResolveMessage::output(&solutions.join(", ")))
+                    })
+                    .collect()
+            },
+            Ok(SystemSolution::NoSolution) => report(&system.lines, ResolveMessage::error("System of equations has no solution")),
+            Ok(SystemSolution::Infinite) => report(&system.lines, ResolveMessage::info("System of equations has infinitely many solutions")),
+            // Nonlinear systems are not supported, resolve the lines one by one instead
+            Err(SolveError::NotLinear | SolveError::NotPolynomial) => {
+                self.system = system;
+                self.finish()
+            },
+            Err(SolveError::Error(err)) => report(&system.lines, ResolveMessage::error(&err)),
+        }
+    }
+
     pub fn resolve_line(&mut self, mut root: ASTNode) -> Vec<ResolveMessage> {
         let mut out = Vec::new();
 
@@ -46,7 +438,7 @@ impl Resolver {
         let mut has_empty = false;
         post_order_mut(&mut root, &mut |x| {
             if x.node_type == ASTNodeType::Empty {
-                out.push(ResolveMessage::error("Wrong usage of operation"));
+                out.push(ResolveMessage::error("Wrong usage of operation").with_span(x.span));
                 out.push(ResolveMessage::error("Note: This usually means you forgot a +, -, /, etc."));
                 has_empty = true;
             }
@@ -61,6 +453,14 @@ impl Resolver {
             return out;
         }
 
+        if let Some(error) = self.reassigned_constant(&root).or_else(|| self.listed_arguments(&root)).or_else(|| self.units_in_decimal_mode(&root)) {
+            return vec![error];
+        }
+
+        self.exact_literals(&mut root);
+
+        let symbolic = self.is_symbolic_call(&root);
+
         // Resolve the root and check for errors
         let resolve_result = match &root.node_type {
             // if the root is an assignment, only resolve the right side
@@ -68,8 +468,8 @@ impl Resolver {
                 if !matches!(root.children[0].node_type, ASTNodeType::Equality) {
                     return vec![ResolveMessage::error("Assignment must contain an equality in a correct place")];
                 }
-                // if root.children[0]
-                self.resolve_expression(&mut root.children[0].children[1])
+                // The function body is resolved by process_fn, where the parameters shadow the namespace
+                Ok(HashSet::new())
             }
             _ => self.resolve_expression(&mut root),
         };
@@ -90,10 +490,13 @@ impl Resolver {
         match &root.node_type {
             ASTNodeType::Equality => {
                 match encountered_unknowns.len() {
-                    1 => out.push(self.resolve_equation(&mut root)), // equation
+                    1 => { // equation
+                        let unknown = encountered_unknowns.into_iter().next().unwrap();
+                        out.extend(self.resolve_equation(&mut root, &unknown));
+                    }
                     0 => { // equality, print true or false
                         if root.node_type == ASTNodeType::Equality {
-                            out.push(ResolveMessage::output(&format!("{}", root.children[0] == root.children[1])));
+                            out.push(self.check_equality(&root.children[0], &root.children[1]));
                         } else {
                             out.push(ResolveMessage::error("Could not resolve"));
                         }
@@ -101,6 +504,18 @@ impl Resolver {
                     _ => out.push(ResolveMessage::error("Could not resolve an equation with more than one unknown")) // equation with more than one unknown
                 }
             }
+            ASTNodeType::Comparison(comparison) => {
+                match encountered_unknowns.len() {
+                    1 => { // inequality
+                        let unknown = encountered_unknowns.into_iter().next().unwrap();
+                        out.extend(self.resolve_inequality(&root, *comparison, &unknown));
+                    }
+                    0 => out.push(ResolveMessage::error("Could not resolve comparison")),
+                    _ => out.push(ResolveMessage::error("Could not resolve an inequality with more than one unknown"))
+                }
+            }
+            // A comparison of values, printed like an equality
+            ASTNodeType::Delimeter(Token::Boolean(value)) => out.push(ResolveMessage::output(&value.to_string())),
             ASTNodeType::Assignment => { // assignment
                 match &root.children[0].node_type {
                     &ASTNodeType::Equality => {
@@ -110,13 +525,14 @@ impl Resolver {
                         match &fn_declaration.node_type {
                             ASTNodeType::Function(name) => {
                                 let name = name.clone();
-                                let processed_fn = self.process_fn(&fn_declaration.children[0], body);
-                                if let Err(error) = processed_fn {
-                                    out.push(error);
-                                } else {
-                                    let (processed_body, arguments) = processed_fn.unwrap();
-                                    self.namespace.insert(name.to_string(), NamespaceElement::Function(processed_body));
-                                    out.push(ResolveMessage::output(&format!("{}({}) = [...]", name, arguments.join(", "))));
+                                // A function without parameters is declared with empty parens, ex. let f() = 2
+                                let args = fn_declaration.children.first().cloned().unwrap_or_else(|| ASTNode::new(ASTNodeType::List, vec![]));
+                                match self.process_fn(&args, body) {
+                                    Ok(function) => {
+                                        out.push(ResolveMessage::output(&format!("{} = [...]", function.signature(&name))));
+                                        self.namespace.insert(name, NamespaceElement::Function(function));
+                                    }
+                                    Err(error) => out.push(error)
                                 }
                             }
                             _ => out.push(ResolveMessage::error("Assignment requires function on left side"))
@@ -126,15 +542,21 @@ impl Resolver {
                 }
             }
             _ => { // expression
-                if encountered_unknowns.len() == 0 {
-                    match root.node_type {
-                        ASTNodeType::Delimeter(Token::Number(num)) => {
-                            out.push(ResolveMessage::output(&format!("? = {}", num)));
+                if let Some(function) = self.user_function(&root) {
+                    // A function name on its own lists the function
+                    out.push(ResolveMessage::output(&function));
+                } else if encountered_unknowns.is_empty() {
+                    match self.format_special_value(&root).or_else(|| format_value(&root)) {
+                        Some(val) => {
+                            out.push(ResolveMessage::output(&format!("? = {}", val)));
                         },
-                        _ => {
+                        None => {
                             out.push(ResolveMessage::error("Could not resolve expression")); // TODO: Drill down on error
                         }
                     }
+                } else if symbolic {
+                    // The result of a symbolic function is an expression, not a value
+                    out.push(ResolveMessage::output(&format!("? = {}", root)));
                 } else {
                     out.push(ResolveMessage::error("Could not resolve expression with unknown"));
                     out.push(ResolveMessage::info("Hint: To solve for an unknown, make this into an equation"));
@@ -263,42 +685,214 @@ impl Resolver {
         out
     }
 
-    /// Expands functions from the namespace
+    // Expands functions from the namespace
     // pub fn expand_functions(&self, expr: &mut ASTNode) -> Result<(), ResolveMessage> {
 
     //     todo!()
     // }
 
+    /// Transforms calls like diff(x^2, x) in a function body when it is defined, the rest of the body is resolved when it is called
+    fn resolve_symbolic_calls(&self, node: &mut ASTNode) -> Result<(), ResolveMessage> {
+        if self.is_symbolic_call(node) {
+            return self.resolve_expression(node).map(|_| ()).map_err(|mut errors| errors.remove(0));
+        }
+        node.children.iter_mut().try_for_each(|x| self.resolve_symbolic_calls(x))
+    }
+
+    /// Lists a user function by its name, ex. f(x, y) = x * y
+    fn user_function(&self, node: &ASTNode) -> Option<String> {
+        let name = match &node.node_type {
+            ASTNodeType::Delimeter(Token::Name(name)) => name,
+            _ => return None
+        };
+        match self.namespace.get(name) {
+            Some(NamespaceElement::Function(function)) => Some(format!("{} = {}", function.signature(name), function.expression())),
+            _ => None
+        }
+    }
+
+    /// Checks if the node calls a builtin which works with unknowns, ex. simplify(x + x)
+    fn is_symbolic_call(&self, node: &ASTNode) -> bool {
+        match &node.node_type {
+            ASTNodeType::Function(name) => builtins::get_symbolic_builtin(name).is_some() && !self.namespace.contains_key(name),
+            _ => false
+        }
+    }
+
+    /// Finds the bound variable of calls like sum(k^2, k, 1, 10), a sum of four values has none
+    fn bound_variable(&self, node: &ASTNode) -> Option<String> {
+        let name = match &node.node_type {
+            ASTNodeType::Function(name) if builtins::BOUND_BUILTINS.contains(&name.as_str()) && !self.namespace.contains_key(name) => name,
+            _ => return None
+        };
+        let args = list_node_to_vec(node.children.first()?);
+        match args.as_slice() {
+            [body, ASTNode { node_type: ASTNodeType::Delimeter(Token::Name(variable)), .. }, _, _] => {
+                let is_aggregate = builtins::get_builtin(name).is_some() && self.namespace.contains_key(variable) && !contains_name(body, variable);
+                (!is_aggregate).then(|| variable.clone())
+            }
+            _ => None
+        }
+    }
+
+    /// Checks if the node is a call of the if(condition, a, b) builtin
+    fn is_conditional(&self, node: &ASTNode) -> bool {
+        matches!(&node.node_type, ASTNodeType::Function(name) if name == "if" && !self.namespace.contains_key(name))
+    }
+
+    /// Resolves the branch of if(condition, a, b) chosen by the condition, the other one is never resolved,
+    /// so that recursive functions can stop, ex. let f(n) = if(n = 0, 1, n f(n - 1))
+    fn resolve_conditional(&self, node: &mut ASTNode, unknowns: &mut HashSet<String>) -> Result<(), Vec<ResolveMessage>> {
+        let args = call_arguments(node).map_err(|x| vec![x])?;
+        if args.len() != 3 {
+            return Err(vec![ResolveMessage::error(&format!("if expects 3 arguments, got {}", args.len())).with_span(node.span)]);
+        }
+        let mut condition = args[0].clone();
+        let condition_unknowns = self.resolve_expression(&mut condition)?;
+        if !condition_unknowns.is_empty() {
+            unknowns.extend(condition_unknowns);
+            return Ok(());
+        }
+        // An equality is compared like in an equality check, ex. if(n = 0, 1, n f(n - 1))
+        if condition.node_type == ASTNodeType::Equality {
+            condition = ASTNode::delimeter(Token::Boolean(self.values_equal(&condition.children[0], &condition.children[1])));
+        }
+        // Any number other than zero is true, ex. the result of a comparison
+        let branch = match (&condition.node_type, as_quantity(&condition)) {
+            (ASTNodeType::Delimeter(Token::Boolean(true)), _) => &args[1],
+            (ASTNodeType::Delimeter(Token::Boolean(false)), _) => &args[2],
+            (_, Some(value)) if value.value != 0. => &args[1],
+            (_, Some(_)) => &args[2],
+            (_, None) => return Err(vec![ResolveMessage::error("The condition of if must be a number or a comparison").with_span(condition.span)])
+        };
+        let mut branch = branch.clone();
+        unknowns.extend(self.resolve_expression(&mut branch)?);
+        *node = branch.with_span(node.span);
+        Ok(())
+    }
+
+    /// Evaluates conditions, integrals, sums and products before the rest of the expression.
+    /// Only the chosen branch of a condition is resolved, and the bound variable of a sum is not replaced with a value of the same name from the namespace.
+    /// Calls which cannot be evaluated yet are left as they are, their unknowns are added to the set.
+    fn resolve_deferred_calls(&self, node: &mut ASTNode, unknowns: &mut HashSet<String>) -> Result<(), Vec<ResolveMessage>> {
+        if self.is_conditional(node) {
+            return self.resolve_conditional(node, unknowns);
+        }
+        let variable = match self.bound_variable(node) {
+            Some(variable) => variable,
+            None => return node.children.iter_mut().try_for_each(|x| self.resolve_deferred_calls(x, unknowns))
+        };
+        match self.evaluate_bound_call(node, &variable).map_err(|x| x.into_iter().map(|x| x.with_span(node.span)).collect::<Vec<_>>())? {
+            Some(result) => *node = quantity_node(result).with_span(node.span),
+            None => post_order(node, &mut |x| match &x.node_type {
+                ASTNodeType::Delimeter(Token::Name(name)) if *name != variable && !self.namespace.contains_key(name) => {
+                    unknowns.insert(name.clone());
+                }
+                _ => ()
+            })
+        }
+        Ok(())
+    }
+
+    /// Evaluates a call with a bound variable, `None` if it has other unknowns
+    fn evaluate_bound_call(&self, node: &ASTNode, variable: &str) -> Result<Option<Quantity>, Vec<ResolveMessage>> {
+        let name = match &node.node_type {
+            ASTNodeType::Function(name) => name.as_str(),
+            _ => unreachable!("bound variables belong to function calls")
+        };
+        let args = list_node_to_vec(&node.children[0]);
+        let error = |message: String| Err(vec![ResolveMessage::error(&message)]);
+
+        let evaluate = |expression: &ASTNode, value: Option<f64>| -> Result<Option<Quantity>, Vec<ResolveMessage>> {
+            let mut expression = expression.clone();
+            if let Some(value) = value {
+                substitute(&mut expression, variable, &ASTNode::number(value));
+            }
+            if !self.resolve_expression(&mut expression)?.is_empty() {
+                return Ok(None);
+            }
+            as_quantity(&expression).map(Some).ok_or_else(|| vec![ResolveMessage::error(&format!("{} expects real numbers", name))])
+        };
+        let (a, b) = match (evaluate(&args[2], None)?, evaluate(&args[3], None)?) {
+            (Some(a), Some(b)) if a.dimension.is_dimensionless() && b.dimension.is_dimensionless() => (a.value, b.value),
+            (Some(_), Some(_)) => return error(format!("{} expects dimensionless bounds", name)),
+            _ => return Ok(None)
+        };
+        if name == "integrate" {
+            // The integrand is not evaluated at the bounds, where it can have a singularity, ex. 1 / sqrt(x) from 0 to 1
+            let first = match evaluate(&args[0], Some((a + b) / 2.)) {
+                Ok(Some(first)) => first,
+                Ok(None) => return Ok(None),
+                Err(_) => return error(solver::UNDEFINED_INTEGRAL.into())
+            };
+            return match solver::integrate(|x| evaluate(&args[0], Some(x)).ok().flatten().map(|x| x.value), a, b) {
+                Ok(value) => Ok(Some(Quantity { value, ..first })),
+                Err(err) => error(err)
+            };
+        }
+        let first = match evaluate(&args[0], Some(a))? {
+            Some(first) => first,
+            None => return Ok(None)
+        };
+        if a.fract() != 0. || b.fract() != 0. {
+            return error(format!("{} expects integer bounds", name));
+        }
+        if b - a >= MAX_SERIES_TERMS {
+            return error(format!("{} has more than {} terms", name, MAX_SERIES_TERMS));
+        }
+        // An empty sum is 0 and an empty product is 1
+        let (empty, operate): (f64, QuantityOperation) = match name {
+            "sum" => (0., |a, b| a.checked_add(b).map_err(|x| ResolveMessage::error(&x))),
+            _ => (1., |a, b| Ok(a * b)),
+        };
+        if b < a {
+            return Ok(Some(Quantity::number(empty)));
+        }
+        let mut result = first;
+        for k in (a as i64 + 1)..=(b as i64) {
+            match evaluate(&args[0], Some(k as f64))? {
+                Some(term) => result = operate(result, term).map_err(|x| vec![x])?,
+                None => return Ok(None)
+            }
+        }
+        Ok(Some(result))
+    }
+
     /// Returns a result of the set of encountered unknowns or a resolve message containing an error
     pub fn resolve_expression(&self, expr: &mut ASTNode) -> Result<HashSet<String>, Vec<ResolveMessage>> {
         let mut encountered_unknowns = HashSet::<String>::new();
         let mut errors: Vec<ResolveMessage> = vec![];
-
-        post_order_mut(expr, &mut |x| {
+        self.resolve_deferred_calls(expr, &mut encountered_unknowns)?;
+
+        // The insides of deferred calls are only resolved when the call is evaluated
+        walkers::post_order_mut_pruned(expr, &|x| self.bound_variable(x).is_none() && !self.is_conditional(x), &mut |x| {
+            let special = match self.number_mode {
+                NumberMode::Decimal { precision } => resolve_decimals(x, precision),
+                _ => resolve_rationals(x),
+            }.or_else(|| resolve_complex(x)).or_else(|| resolve_matrices(x)).or_else(|| resolve_lists(x));
+            if let Some(result) = special {
+                match result {
+                    Ok(node) => *x = node,
+                    Err(err) => errors.push(err),
+                }
+                return;
+            }
             match &x.node_type {
-                ASTNodeType::Sum => { resolve_numbers(x, |a, b| Ok(a + b)); },
-                ASTNodeType::Difference => { resolve_numbers(x, |a, b| Ok(a - b)); },
-                ASTNodeType::Product => { resolve_numbers(x, |a, b| Ok(a * b)); },
-                ASTNodeType::Quotient => {
-                    let result = resolve_numbers(
-                        x,
-                        |a, b| if b != 0. { Ok(a / b) } else { Err(ResolveMessage::error("Divide by zero")) } // TODO: Drilldown
-                    );
-                    if let Some(err) = result {
-                        errors.push(err);
-                    }
+                ASTNodeType::Sum | ASTNodeType::Difference | ASTNodeType::Product | ASTNodeType::Quotient | ASTNodeType::Power => {
+                    let operate = quantity_operation(&x.node_type).unwrap();
+                    errors.extend(resolve_numbers(x, operate));
                 },
-                ASTNodeType::Power => { resolve_numbers(x, |a, b| Ok(f64::powf(a, b))); },
                 ASTNodeType::Function(f_name) => { // TODO: Move this out of here to a different loop
                     // currently if the name is in the namespace, it is multiplication
                     if let Some(element) = self.namespace.get(f_name.as_str()) {
                         match element {
                             // the "function" is actually implied multiplication, we just multiply
-                            NamespaceElement::Number(_) => {
+                            NamespaceElement::Number(_) | NamespaceElement::Quantity(_) | NamespaceElement::Rational(_) | NamespaceElement::BigNum(_) | NamespaceElement::Complex(_) | NamespaceElement::Matrix(_) | NamespaceElement::List(_) => {
                                 resolve_numbers(x, |a, b| Ok(a * b));
                             }
-                            NamespaceElement::Function(body) => {
-                                match self.resolve_fn(x, body) {
+                            NamespaceElement::Boolean(_) => errors.push(ResolveMessage::error(&format!("{} is not a function", f_name)).with_span(x.span)),
+                            NamespaceElement::Function(function) => {
+                                match self.resolve_fn(x, function) {
                                     Ok(set) => encountered_unknowns.extend(set),
                                     Err(mut new_errors) => errors.append(&mut new_errors)
                                 }
@@ -307,13 +901,25 @@ impl Resolver {
                                 // }
                             },
                         }
+                    } else if let Some(builtin) = builtins::get_builtin(f_name) {
+                        errors.extend(resolve_builtin(x, builtin));
+                    } else if let Some(builtin) = builtins::get_matrix_builtin(f_name) {
+                        errors.extend(resolve_matrix_builtin(x, builtin));
+                    } else if let Some(builtin) = builtins::get_symbolic_builtin(f_name) {
+                        errors.extend(resolve_symbolic_builtin(x, builtin));
                     }
                 },
                 ASTNodeType::Empty => (), // leave it be
-                ASTNodeType::List => (), // TODO: Think what should be the behavior here
+                // Nested lists are flattened, so a list of values is a list value, ex. 10, 20, 30
+                ASTNodeType::List => x.children = list_node_to_vec(x),
+                ASTNodeType::Vector => errors.extend(resolve_vector(x)),
                 ASTNodeType::FnArgument(_) => (), // impossible to be here
                 ASTNodeType::Assignment => (), // the end
                 ASTNodeType::Equality => (), // the end
+                ASTNodeType::Unit(_) | ASTNodeType::Quantity(_) | ASTNodeType::Rational(_) | ASTNodeType::Complex(_) | ASTNodeType::Matrix(_) => (), // values, like numbers
+                ASTNodeType::Conversion => errors.extend(resolve_conversion(x)),
+                ASTNodeType::Comparison(comparison) => errors.extend(self.resolve_comparison(x, *comparison)),
+                ASTNodeType::Logic(logic) => errors.extend(resolve_logic(x, *logic)),
                 // ASTNodeType::Assignment => {
                 //     let equality = &mut x.children[0];
                 //     match equality.node_type {
@@ -338,19 +944,16 @@ impl Resolver {
                 //     }
                 // }
                 ASTNodeType::Delimeter(delimeter) => {
-                    match delimeter {
-                        Token::Name(name) => {
-                            if let Some(element) = self.namespace.get(name) {
-                                // The element exists is the namespace
-                                if let Some(node) = element.as_astnode() {
-                                    // The namespace element can be represented as a node
-                                    *x = node;
-                                }
-                            } else {
-                                encountered_unknowns.insert(name.clone());
+                    if let Token::Name(name) = delimeter {
+                        if let Some(element) = self.namespace.get(name) {
+                            // The element exists is the namespace
+                            if let Some(node) = element.as_astnode() {
+                                // The namespace element can be represented as a node
+                                *x = node.with_span(x.span);
                             }
+                        } else {
+                            encountered_unknowns.insert(name.clone());
                         }
-                        _ => ()
                     };
                 },
             };
@@ -362,152 +965,397 @@ impl Resolver {
         }
     }
 
-    pub fn resolve_equation(&mut self, root: &mut ASTNode) -> ResolveMessage {
-        // TODO: An assumption is made here, that the unknown is a number
-        let (mut unknown_side, mut other_side) = match (&root.children[0].node_type, &root.children[1].node_type) {
-            (_, ASTNodeType::Delimeter(Token::Number(_))) | (ASTNodeType::Delimeter(Token::Number(_)), _) => {
-                // Decide what is the unknown side and what is the other side
-                let (mut unknown_side, mut other_side) = (root.children.pop().unwrap(), root.children.pop().unwrap());
-                if matches!(unknown_side.node_type, ASTNodeType::Delimeter(Token::Number(_))) {
-                    std::mem::swap(&mut unknown_side, &mut other_side);
+    /// Solves an equation in a single unknown, binding the solution in the namespace
+    pub fn resolve_equation(&mut self, root: &mut ASTNode, unknown: &str) -> Vec<ResolveMessage> {
+        let display_unit = conversion_unit(&root.children[1]).or_else(|| conversion_unit(&root.children[0]));
+        // x = 1/3 keeps the exact value, z = 3 + 4i the complex one, v = (1, 2) the vector and x = 1, 2 the list,
+        // the solver only works with real floats
+        for (name, value) in [(&root.children[0], &root.children[1]), (&root.children[1], &root.children[0])] {
+            let element = match &value.node_type {
+                ASTNodeType::Rational(rational) => NamespaceElement::Rational(rational.clone()),
+                ASTNodeType::Delimeter(Token::Decimal(decimal)) => NamespaceElement::BigNum(decimal.clone()),
+                ASTNodeType::Complex(complex) => NamespaceElement::Complex(*complex),
+                ASTNodeType::Matrix(matrix) => NamespaceElement::Matrix(matrix.clone()),
+                ASTNodeType::Delimeter(Token::Boolean(value)) => NamespaceElement::Boolean(*value),
+                ASTNodeType::List => match list_values(value) {
+                    Some(values) => NamespaceElement::List(values),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            if matches!(name.node_type, ASTNodeType::Delimeter(Token::Name(_))) {
+                self.namespace.insert(unknown.to_string(), element);
+                return vec![ResolveMessage::output(&format!("{} = {}", unknown, self.format_special_value(value).or_else(|| format_value(value)).unwrap()))];
+            }
+        }
+        self.last_trace = None;
+        // With tracing, undoing the operations is preferred to the polynomial solver, as its steps are easy to follow
+        if self.trace {
+            let (mut message, mut trace) = self.isolate_unknown(&mut root.clone());
+            // An unknown appearing several times, ex. 2 x + 3 = x + 7, is collected into a x + b = 0 instead
+            if message.msg_type == ResolveMessageType::Error {
+                if let Ok((a, b)) = solver::linear_form(&root.children[0], &root.children[1], unknown) {
+                    trace = SolutionTrace::new(root.clone());
+                    let solution = trace.collect_linear(unknown, a, b);
+                    message = self.assign_solution(unknown, solution, display_unit.clone(), root);
+                }
+            }
+            if message.msg_type != ResolveMessageType::Error {
+                let mut out = vec![message];
+                out.extend(trace.steps.iter().map(|x| ResolveMessage::info(&x.operation)));
+                self.last_trace = Some(trace);
+                return out;
+            }
+        }
+        match solver::solve_polynomial(&root.children[0], &root.children[1], unknown) {
+            Ok(Solution::Roots(roots)) => self.assign_roots(unknown, &roots, display_unit, root),
+            Ok(Solution::NoSolution) => vec![ResolveMessage::error("Equation has no solution")],
+            Ok(Solution::Infinite) => vec![ResolveMessage::info(&format!("Equation has infinitely many solutions, it is true for every {}", unknown))],
+            // Equations like 2 / x = 10 can still be solved by undoing operations on the unknown,
+            // anything else, ex. sin(x) = 0.5, is solved numerically
+            Err(SolveError::NotPolynomial | SolveError::NotLinear) => {
+                let equation = root.clone();
+                match self.isolate_unknown(root).0 {
+                    message if message.msg_type == ResolveMessageType::Error => self.resolve_numerically(&equation, unknown),
+                    message => vec![message],
                 }
-                (unknown_side, other_side)
             },
+            Err(SolveError::Error(err)) => vec![ResolveMessage::error(&err)],
+        }
+    }
+
+    /// Prints whether two values are equal, values equal only within the tolerance are marked, ex. true (≈)
+    fn check_equality(&self, a: &ASTNode, b: &ASTNode) -> ResolveMessage {
+        if a == b {
+            ResolveMessage::output("true")
+        } else {
+            ResolveMessage::output(if self.values_equal(a, b) { "true (≈)" } else { "false" })
+        }
+    }
+
+    /// Evaluates a comparison of two values to a boolean, comparisons with unknowns are left as they are.
+    /// Values equal within the tolerance are equal, ex. 0.1 + 0.2 <= 0.3 is true and 0.1 + 0.2 != 0.3 is false
+    fn resolve_comparison(&self, node: &mut ASTNode, comparison: Comparison) -> Option<ResolveMessage> {
+        let unordered = |x: &ASTNode| matches!(x.node_type, ASTNodeType::Complex(_) | ASTNodeType::Matrix(_) | ASTNodeType::Delimeter(Token::Boolean(_)));
+        let (a, b) = (&node.children[0], &node.children[1]);
+        let result = match (as_quantity(a), as_quantity(b)) {
+            (Some(x), Some(y)) if x.dimension != y.dimension => {
+                return Some(ResolveMessage::error(&format!("Cannot compare [{}] with [{}]", x.dimension, y.dimension)).with_span(node.span));
+            }
+            (Some(x), Some(y)) => {
+                let equal = self.tolerance.equal(x.value, y.value);
+                match comparison {
+                    Comparison::NotEqual => !equal,
+                    Comparison::LessEqual | Comparison::GreaterEqual => equal || comparison.holds(x.value - y.value),
+                    Comparison::Less | Comparison::Greater => !equal && comparison.holds(x.value - y.value),
+                }
+            }
+            // Complex numbers, matrices and booleans have no order, but they can be unequal
+            (x, y) if (unordered(a) || x.is_some()) && (unordered(b) || y.is_some()) => match comparison {
+                Comparison::NotEqual => !self.values_equal(a, b),
+                _ => return Some(ResolveMessage::error("Only real numbers can be ordered").with_span(node.span)),
+            },
+            _ => return None,
+        };
+        *node = ASTNode::delimeter(Token::Boolean(result)).with_span(node.span);
+        None
+    }
+
+    /// Whether two values are equal within the tolerance
+    fn values_equal(&self, a: &ASTNode, b: &ASTNode) -> bool {
+        let equal = |x: f64, y: f64| self.tolerance.equal(x, y);
+        a == b || match (&a.node_type, &b.node_type, as_quantity(a), as_quantity(b)) {
+            (_, _, Some(x), Some(y)) => x.dimension == y.dimension && equal(x.value, y.value),
+            (ASTNodeType::Complex(x), ASTNodeType::Complex(y), _, _) => equal(x.re, y.re) && equal(x.im, y.im),
+            (ASTNodeType::Matrix(x), ASTNodeType::Matrix(y), _, _) => (x.rows(), x.cols()) == (y.rows(), y.cols())
+                && (0..x.rows()).all(|i| (0..x.cols()).all(|j| equal(x.get(i, j), y.get(i, j)))),
+            _ => false,
+        }
+    }
+
+    /// Solves a linear inequality in a single unknown, ex. 2 x + 1 < 7 is x < 3
+    fn resolve_inequality(&self, root: &ASTNode, comparison: Comparison, unknown: &str) -> Vec<ResolveMessage> {
+        let display_unit = conversion_unit(&root.children[1]).or_else(|| conversion_unit(&root.children[0]));
+        match solver::solve_linear_inequality(&root.children[0], &root.children[1], unknown, comparison) {
+            Ok(Interval::Bound(comparison, bound)) => {
+                vec![ResolveMessage::output(&format!("{} {} {}", unknown, comparison.symbol(), format_solution(bound, &display_unit)))]
+            }
+            Ok(Interval::All) => vec![ResolveMessage::info(&format!("Inequality is true for every {}", unknown))],
+            Ok(Interval::Empty) => vec![ResolveMessage::error("Inequality has no solution")],
+            Err(SolveError::NotPolynomial | SolveError::NotLinear) => vec![ResolveMessage::error("Only linear inequalities can be solved")],
+            Err(SolveError::Error(err)) => vec![ResolveMessage::error(&err)],
+        }
+    }
+
+    /// Formats values which are not quantities, exact values according to the number mode
+    fn format_special_value(&self, node: &ASTNode) -> Option<String> {
+        match (&node.node_type, self.number_mode) {
+            (ASTNodeType::Complex(complex), _) => Some(complex.to_string()),
+            (ASTNodeType::Matrix(matrix), _) => Some(matrix.to_string()),
+            (ASTNodeType::Delimeter(Token::Decimal(decimal)), _) => Some(decimal.to_string()),
+            (ASTNodeType::Delimeter(Token::Boolean(value)), _) => Some(value.to_string()),
+            (ASTNodeType::Rational(rational), NumberMode::Rational(RationalFormat::Decimal)) => {
+                Some(rational.to_decimal_string().unwrap_or_else(|| format_number(rational.to_f64())))
+            }
+            (ASTNodeType::Rational(rational), _) => Some(rational.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Solves an equation where the unknown appears once, by undoing the operations around it
+    fn isolate_unknown(&mut self, root: &mut ASTNode) -> (ResolveMessage, SolutionTrace) {
+        let equation = root.clone();
+        let mut trace = SolutionTrace::new(root.clone());
+        // TODO: An assumption is made here, that the unknown is a number
+        let (unknown_index, mut other_side_val) = match (as_quantity(&root.children[0]), as_quantity(&root.children[1])) {
+            (_, Some(val)) => (0, val),
+            (Some(val), _) => (1, val),
             _ => {
                 // If the equation is not in the above form, it cannot be solved
-                return ResolveMessage::error("Equation could not be solved");
+                return (ResolveMessage::error("Equation could not be solved"), trace);
             }
         };
+        // x = 3 [km] to [mi] should print x in miles
+        let display_unit = conversion_unit(&root.children[1 - unknown_index]);
+        let mut unknown_side = root.children.swap_remove(unknown_index);
 
         loop {
             if unknown_side.children.len() == 2 {
                 // right and left are in reverse, as items are popped from the right
                 let (right, left) = (unknown_side.children.pop().unwrap(), unknown_side.children.pop().unwrap());
-                let (unknown_on_left, unknown_side_val) = match (&left.node_type, &right.node_type) {
-                    (ASTNodeType::Delimeter(Token::Number(a)), _) => (false, *a),
-                    (_, ASTNodeType::Delimeter(Token::Number(a))) => (true, *a),
-                    _ => return ResolveMessage::error("Side other to unknown is not a number")
+                let (unknown_on_left, unknown_side_val) = match (as_quantity(&left), as_quantity(&right)) {
+                    (Some(a), _) => (false, a),
+                    (_, Some(a)) => (true, a),
+                    _ => return (ResolveMessage::error("Side other to unknown is not a number"), trace)
                 };
 
-                let other_side_val = match &mut other_side.node_type {
-                    ASTNodeType::Delimeter(Token::Number(b)) => b,
-                    _ => return ResolveMessage::error("Side other to unknown is not a number")
-                };
-                match &unknown_side.node_type {
-                    ASTNodeType::Sum => {
-                        *other_side_val -= unknown_side_val;
-                        unknown_side = if unknown_on_left { left } else { right };
-                    },
+                let result = match &unknown_side.node_type {
+                    ASTNodeType::Sum => other_side_val.checked_sub(unknown_side_val),
                     ASTNodeType::Difference => {
-                        *other_side_val += unknown_side_val;
-                        unknown_side = if unknown_on_left { left } else { right };
-                    },
-                    ASTNodeType::Product => {
-                        *other_side_val /= unknown_side_val;
-                        unknown_side = if unknown_on_left { left } else { right };
+                        if unknown_on_left { // x - 2 = 10 -> x = 12
+                            other_side_val.checked_add(unknown_side_val)
+                        } else { // 2 - x = 10 -> x = 2 - 10
+                            unknown_side_val.checked_sub(other_side_val)
+                        }
                     },
+                    ASTNodeType::Product => Ok(other_side_val / unknown_side_val),
                     ASTNodeType::Quotient => {
                         if unknown_on_left { // x / 2 = 10 -> x = 20
-                            *other_side_val *= unknown_side_val;
-                            unknown_side = left;
+                            Ok(other_side_val * unknown_side_val)
                         } else { // 2 / x = 10 -> x = 2 / 10
-                            *other_side_val = unknown_side_val / *other_side_val;
-                            unknown_side = right;
+                            Ok(unknown_side_val / other_side_val)
                         }
                     },
-                    ASTNodeType::Power => return ResolveMessage::error("Cannot evaluate powers of unknowns"), // TODO: resolve x^(2n+1)
-                    _ => ()
+                    ASTNodeType::Power => return (ResolveMessage::error("Cannot evaluate powers of unknowns"), trace), // TODO: resolve x^(2n+1)
                     // ASTNodeType::Function(_) => todo!(), // all functions should have been evaluated
                     // ASTNodeType::Empty => (), // all empty objects should have been converted to parse errors
-                }
+                    _ => return (ResolveMessage::error("Could not resolve equation"), trace)
+                };
+                other_side_val = match result {
+                    Ok(val) => val,
+                    Err(err) => return (ResolveMessage::error(&err).with_span(unknown_side.span), trace),
+                };
+                let operation = unknown_side.node_type.clone();
+                unknown_side = if unknown_on_left { left } else { right };
+                trace.undo(&operation, unknown_side_val, unknown_on_left, &unknown_side, other_side_val, unknown_index);
             } else {
                 match &unknown_side.node_type {
                     ASTNodeType::Delimeter(Token::Name(name)) => {
                         // we have arrived at the end
-                        match &other_side.node_type {
-                            ASTNodeType::Delimeter(Token::Number(num)) => {
-                                // let clone = name.clone();
-                                self.namespace.insert(name.to_string(), NamespaceElement::Number(*num));
-                                return ResolveMessage::output(&format!("{} = {}", name, num));
-                            }
-                            _ => {
-                                return ResolveMessage::error("Could not resolve equation"); // TODO Drill down on error
-                            }
-                        }
+                        return (self.assign_solution(name, other_side_val, display_unit, &equation), trace);
                     },
                     // ASTNodeType::Equality => return ResolveMessage::error("Multiple equality is disallowed"),
-                    _ => () // TODO Drill down on error
+                    _ => return (ResolveMessage::error("Could not resolve equation"), trace) // TODO Drill down on error
                 }
             }
         }
     }
 
-    /// Processes the function body, substituting and replacing argument names with argument placeholders
-    /// @returns Result of a tuple of the resulting Function body and a vector of argument names
-    pub fn process_fn(&mut self, args: &ASTNode, mut body: ASTNode) -> Result<(ASTNode, Vec<String>), ResolveMessage> {
-        let processed_args = process_fn_args(args)?;
-        let mut unknown_name: String = String::new();
-
-        walkers::post_order_mut(&mut body, &mut |x| match &x.node_type {
-            ASTNodeType::Delimeter(Token::Name(name)) =>
-                if let Some(index) = processed_args.iter().position(|x| x == name) { // could be optimised with a map assigning strings to arg numbers
-                    *x = ASTNode::new(ASTNodeType::FnArgument(index), vec![]);
-                } else {
-                    unknown_name = name.clone();
-                },
-            _ => ()
+    /// Finds a root of the equation numerically, substituting values for the unknown
+    fn resolve_numerically(&mut self, equation: &ASTNode, unknown: &str) -> Vec<ResolveMessage> {
+        let root = solver::find_root(|x| {
+            let (mut left, mut right) = (equation.children[0].clone(), equation.children[1].clone());
+            for side in [&mut left, &mut right] {
+                substitute(side, unknown, &ASTNode::number(x));
+                if !self.resolve_expression(side).ok()?.is_empty() {
+                    return None;
+                }
+            }
+            Some(as_quantity(&left)?.checked_sub(as_quantity(&right)?).ok()?.value)
         });
-        if unknown_name != "" {
-            Err(ResolveMessage::error(&format!("Unknown name: {}", unknown_name)))
-        } else {
-            Ok((body, processed_args))
+
+        match root {
+            Ok(root) => {
+                self.namespace.insert(unknown.to_string(), NamespaceElement::Number(root.value));
+                let mut out = vec![ResolveMessage::output(&format!("{} ≈ {}", unknown, format_number(root.value)))];
+                if root.tolerance > 0. {
+                    out.push(ResolveMessage::info(&format!("Solved numerically, accurate to ± {:.1e}", root.tolerance)));
+                }
+                out
+            },
+            Err(err) => vec![ResolveMessage::error(&err)],
         }
     }
 
-    fn resolve_fn(&self, node: &mut ASTNode, body: &ASTNode) -> Result<HashSet<String>, Vec<ResolveMessage>> {
-        // let args = process_fn_args(&node.children[0])?;
-        let args = if !&node.children.is_empty() {
-            list_node_to_vec(&node.children[0])
-        } else {
-            vec![]
+    /// Reports every root and binds one of them according to the root policy
+    fn assign_roots(&mut self, name: &str, roots: &[Quantity], display_unit: Option<Unit>, equation: &ASTNode) -> Vec<ResolveMessage> {
+        if roots.is_empty() {
+            return vec![ResolveMessage::error("Equation has no real solution")];
+        }
+        let exact: Vec<Option<Rational>> = roots.iter()
+            .map(|x| self.exact_solutions(&[equation], &[(name, *x)]).map(|mut x| x.remove(0)))
+            .collect();
+        let formatted: Vec<String> = roots.iter().zip(&exact)
+            .map(|(x, exact)| self.format_solution(*x, exact, &display_unit))
+            .collect();
+        let mut out = vec![ResolveMessage::output(&format!("{} = {}", name, formatted.join(", ")))];
+
+        let bound = match self.root_policy {
+            RootPolicy::Unique if roots.len() == 1 => Some(0),
+            RootPolicy::Unique => None,
+            RootPolicy::Smallest => Some(0),
+            RootPolicy::Largest => Some(roots.len() - 1),
         };
+        match bound {
+            Some(index) => { self.namespace.insert(name.to_string(), solution_element(roots[index], &exact[index])); },
+            None => out.push(ResolveMessage::info(&format!("{} has {} solutions, so it was not assigned", name, roots.len()))),
+        }
+        out
+    }
 
-        let mut working_body = body.clone();
+    /// Inserts the solution of an equation into the namespace
+    fn assign_solution(&mut self, name: &str, value: Quantity, display_unit: Option<Unit>, equation: &ASTNode) -> ResolveMessage {
+        let exact = self.exact_solutions(&[equation], &[(name, value)]).map(|mut x| x.remove(0));
+        self.namespace.insert(name.to_string(), solution_element(value, &exact));
+        ResolveMessage::output(&format!("{} = {}", name, self.format_solution(value, &exact, &display_unit)))
+    }
 
-        // std::mem::swap(node, body.clone());
+    /// In exact mode, the solutions as fractions if they satisfy the equations exactly, ex. 3 x = 1 is x = 1/3.
+    /// The solvers work with floats, so the fractions are recovered from them, other solutions stay floats.
+    fn exact_solutions(&self, equations: &[&ASTNode], solutions: &[(&str, Quantity)]) -> Option<Vec<Rational>> {
+        if !matches!(self.number_mode, NumberMode::Rational(_)) {
+            return None;
+        }
+        let values = solutions.iter()
+            .map(|(_, x)| Rational::approximate(x.value, MAX_SOLUTION_DENOMINATOR).filter(|_| x.dimension.is_dimensionless()))
+            .collect::<Option<Vec<_>>>()?;
+        for equation in equations {
+            let mut sides = [equation.children[0].clone(), equation.children[1].clone()];
+            for side in &mut sides {
+                for ((name, _), value) in solutions.iter().zip(&values) {
+                    substitute(side, name, &ASTNode::new(ASTNodeType::Rational(value.clone()), vec![]));
+                }
+                if !self.resolve_expression(side).ok()?.is_empty() {
+                    return None;
+                }
+            }
+            match (&sides[0].node_type, &sides[1].node_type) {
+                (ASTNodeType::Rational(a), ASTNodeType::Rational(b)) if a == b => (),
+                _ => return None,
+            }
+        }
+        Some(values)
+    }
 
-        let mut error: Option<ResolveMessage> = None;
+    /// Formats a solution, as a fraction if it is exact
+    fn format_solution(&self, value: Quantity, exact: &Option<Rational>, display_unit: &Option<Unit>) -> String {
+        match exact {
+            Some(exact) => self.format_special_value(&ASTNode::new(ASTNodeType::Rational(exact.clone()), vec![])).unwrap(),
+            None => format_solution(value, display_unit),
+        }
+    }
 
-        walkers::post_order_mut(
-            &mut working_body,
-            &mut |x| if let ASTNodeType::FnArgument(arg) = x.node_type {
-                if arg + 1 > args.len() { // TODO: Argument count as function signature, and actual message here
-                    error = Some(ResolveMessage::error(&format!("Provided {} args, while this function expects {}", args.len(), args.len() + 1)));
+    /// Processes the function body, substituting and replacing argument names with argument placeholders.
+    /// Other names are captured from the namespace when the function is defined, ex. with a = 2,
+    /// let f(x) = a x is f(x) = 2 x. A parameter shadows a variable of the same name.
+    pub fn process_fn(&mut self, args: &ASTNode, mut body: ASTNode) -> Result<UserFunction, ResolveMessage> {
+        let processed_args = process_fn_args(args)?;
+        let mut unknown_name: String = String::new();
+        let mut unknown_span = None;
+
+        let shadowed: Vec<(String, NamespaceElement)> = processed_args.iter()
+            .filter_map(|name| self.namespace.remove(name).map(|element| (name.clone(), element)))
+            .collect();
+        let resolved = self.resolve_expression(&mut body);
+        self.namespace.extend(shadowed);
+        resolved.map_err(|mut errors| errors.remove(0))?;
+
+        // Bound variables, ex. k in sum(k^2, k, 1, n), are substituted when the call is evaluated
+        let mut bound = vec![];
+        post_order(&body, &mut |x| bound.extend(self.bound_variable(x)));
+
+        walkers::post_order_mut(&mut body, &mut |x| {
+            // The body is substituted into other lines, where its positions are meaningless
+            let span = x.span.take();
+            if let ASTNodeType::Delimeter(Token::Name(name)) = &x.node_type {
+                if bound.contains(name) {
+                    return;
+                }
+                if let Some(index) = processed_args.iter().position(|x| x == name) { // could be optimised with a map assigning strings to arg numbers
+                    *x = ASTNode::new(ASTNodeType::FnArgument(index), vec![]);
+                } else if let Some(node) = self.namespace.get(name).and_then(|x| x.as_astnode()) {
+                    // Names inside deferred calls, ex. the body of a sum, are not resolved above
+                    *x = node;
                 } else {
-                    *x = args[arg].clone();
+                    unknown_name = name.clone();
+                    unknown_span = span;
                 }
             }
-        );
-
-        *node = working_body;
+        });
+        if !unknown_name.is_empty() {
+            Err(ResolveMessage::error(&format!("Unknown name: {}", unknown_name)).with_span(unknown_span))
+        } else {
+            self.resolve_symbolic_calls(&mut body)?;
+            Ok(UserFunction { params: processed_args, body })
+        }
+    }
 
-        let set = self.resolve_expression(node)?;
+    fn resolve_fn(&self, node: &mut ASTNode, function: &UserFunction) -> Result<HashSet<String>, Vec<ResolveMessage>> {
+        call_arguments(node).map_err(|x| vec![x])?;
+        let args = match node.children.first() {
+            // A single parameter takes the argument as a whole, so a list value can be passed to it.
+            // Arguments separated by commas are refused before, by listed_arguments
+            Some(arg) if function.arity() == 1 => vec![arg.clone()],
+            Some(args) => list_node_to_vec(args),
+            None => vec![]
+        };
+        if args.len() != function.arity() {
+            let name = match &node.node_type {
+                ASTNodeType::Function(name) => name.as_str(),
+                _ => "function"
+            };
+            let expected = builtins::Arity::Exact(function.arity()).describe();
+            return Err(vec![ResolveMessage::error(&format!("{} expects {}, got {}", function.signature(name), expected, args.len())).with_span(node.span)]);
+        }
 
-        if error.is_some() {
-            Err(vec![error.unwrap()])
-        } else {
-            Ok(set)
+        if self.call_depth.get() >= self.recursion_limit {
+            return Err(vec![ResolveMessage::error(&format!("Recursion limit of {} nested calls exceeded", self.recursion_limit)).with_span(node.span)]);
         }
+
+        let mut working_body = function.body.clone();
+        walkers::post_order_mut(&mut working_body, &mut |x| if let ASTNodeType::FnArgument(arg) = x.node_type {
+            *x = args[arg].clone();
+        });
+        *node = working_body.with_span(node.span);
+
+        self.call_depth.set(self.call_depth.get() + 1);
+        let result = self.resolve_expression(node);
+        self.call_depth.set(self.call_depth.get() - 1);
+        result
     }
 }
 
 /// Transforms a List ASTNode into a vector of arg names
 pub fn process_fn_args(args: &ASTNode) -> Result<Vec<String>, ResolveMessage> {
     // println!("{:?}", args);
-    let mut out = vec![];
+    let mut out: Vec<String> = vec![];
     let mut has_invalid_args = false;
-    walkers::post_order(&args, &mut |x| {
+    let mut repeated = None;
+    walkers::post_order(args, &mut |x| {
         match &x.node_type { // function args can only have names or other lists
+            ASTNodeType::Delimeter(Token::Name(name)) if out.contains(name) => {
+                repeated = Some(name.clone());
+            }
             ASTNodeType::Delimeter(Token::Name(name)) => {
                 out.push(name.clone());
             }
@@ -517,37 +1365,435 @@ pub fn process_fn_args(args: &ASTNode) -> Result<Vec<String>, ResolveMessage> {
     });
     if has_invalid_args {
         Err(ResolveMessage::error("Invalid function arguments"))
+    } else if let Some(name) = repeated {
+        Err(ResolveMessage::error(&format!("Repeated function argument: {}", name)))
     } else {
         Ok(out)
     }
 }
 
+fn contains_name(node: &ASTNode, name: &str) -> bool {
+    matches!(&node.node_type, ASTNodeType::Delimeter(Token::Name(x)) if x == name) || node.children.iter().any(|x| contains_name(x, name))
+}
+
+/// Replaces every occurrence of a name with a value
+pub fn substitute(node: &mut ASTNode, name: &str, value: &ASTNode) {
+    post_order_mut(node, &mut |x| {
+        if matches!(&x.node_type, ASTNodeType::Delimeter(Token::Name(n)) if n == name) {
+            *x = value.clone().with_span(x.span);
+        }
+    });
+}
+
+/// Flattens nested List nodes into a vector of their elements
 pub fn list_node_to_vec(node: &ASTNode) -> Vec<ASTNode> {
-    let mut out = vec![];
-    walkers::post_order(&node, &mut |x| {
-        match &x.node_type {
-            ASTNodeType::List => (),
-            _ => out.push(x.clone())
+    match &node.node_type {
+        ASTNodeType::List => node.children.iter().flat_map(list_node_to_vec).collect(),
+        _ => vec![node.clone()]
+    }
+}
+
+/// Evaluates a builtin function call once all of its arguments are values
+fn resolve_builtin(node: &mut ASTNode, builtin: &builtins::Builtin) -> Option<ResolveMessage> {
+    let args = match call_arguments(node) {
+        Ok(args) => args,
+        Err(err) => return Some(err)
+    };
+    let is_complex = |x: &ASTNode| matches!(x.node_type, ASTNodeType::Complex(_));
+
+    if args.iter().any(|x| as_matrix(x).is_some()) {
+        return Some(ResolveMessage::error(&format!("{} does not accept matrices", builtin.name)).with_span(node.span));
+    }
+    // Some arguments are unknown, the call cannot be evaluated yet
+    if args.iter().any(|x| as_quantity(x).is_none() && !is_complex(x)) {
+        return None;
+    }
+    if args.iter().any(is_complex) {
+        return match complex_builtin_call(builtin, &args) {
+            Some(result) => {
+                *node = complex_node(result).with_span(node.span);
+                None
+            }
+            None => Some(ResolveMessage::error(&format!("{} does not accept complex numbers", builtin.name)).with_span(node.span))
+        };
+    }
+    let values: Vec<Quantity> = args.iter().filter_map(as_quantity).collect();
+
+    match builtin.call(&values) {
+        Ok(result) => {
+            *node = quantity_node(result).with_span(node.span);
+            None
         }
+        // Out of the real domain, ex. sqrt(-1), the result may still be a complex number
+        Err(err) => match complex_builtin_call(builtin, &args) {
+            Some(result) => {
+                *node = complex_node(result).with_span(node.span);
+                None
+            }
+            None => Some(ResolveMessage::error(&err).with_span(node.span))
+        }
+    }
+}
+
+/// Evaluates the complex variant of a builtin with a single dimensionless argument, `None` if there is no finite result
+fn complex_builtin_call(builtin: &builtins::Builtin, args: &[ASTNode]) -> Option<Complex> {
+    let f = builtins::get_complex_builtin(builtin.name)?;
+    match args {
+        [arg] => Some(f(as_complex(arg)?)).filter(|x| x.is_finite()),
+        _ => None,
+    }
+}
+
+/// Arguments of a call, ex. 1 and 2 in f(1, 2). Arguments without a comma or an operation between them are an error,
+/// ex. f(2 sin(x)), so that none of them is silently dropped.
+fn call_arguments(node: &ASTNode) -> Result<Vec<ASTNode>, ResolveMessage> {
+    match node.children.as_slice() {
+        [] => Ok(vec![]),
+        [args] => Ok(list_node_to_vec(args)),
+        _ => {
+            let name = match &node.node_type {
+                ASTNodeType::Function(name) => name.as_str(),
+                _ => "function"
+            };
+            Err(ResolveMessage::error(&format!("The arguments of {} must be separated by commas or operations", name)).with_span(node.span))
+        }
+    }
+}
+
+/// Replaces a call of a symbolic builtin with the transformed expression, its unknowns stay symbolic
+fn resolve_symbolic_builtin(node: &mut ASTNode, builtin: &builtins::SymbolicBuiltin) -> Option<ResolveMessage> {
+    let args = match call_arguments(node) {
+        Ok(args) => args,
+        Err(err) => return Some(err)
+    };
+    let result = builtin.check_arity(args.len()).and_then(|_| match (builtin.name, args.as_slice()) {
+        ("diff", [expression, variable]) => match variable.node_type {
+            ASTNodeType::Delimeter(Token::Name(_)) | ASTNodeType::FnArgument(_) => differentiate(expression, variable),
+            _ => Err("diff expects an unknown as its second argument".to_string())
+        },
+        (_, [arg]) => Ok(simplify(arg)),
+        _ => unreachable!("the arity is checked")
     });
-    out
+    match result {
+        Ok(result) => {
+            *node = result.with_span(node.span);
+            None
+        }
+        Err(err) => Some(ResolveMessage::error(&err).with_span(node.span))
+    }
+}
+
+/// Evaluates a builtin function of matrices once all of its arguments are values
+fn resolve_matrix_builtin(node: &mut ASTNode, builtin: &builtins::MatrixBuiltin) -> Option<ResolveMessage> {
+    let args = match call_arguments(node) {
+        Ok(args) => args,
+        Err(err) => return Some(err)
+    };
+    if args.iter().any(|x| as_quantity(x).is_some() || matches!(x.node_type, ASTNodeType::Complex(_))) {
+        return Some(ResolveMessage::error(&format!("{} expects matrices or vectors", builtin.name)).with_span(node.span));
+    }
+    // Some arguments are unknown, the call cannot be evaluated yet
+    let values: Vec<Matrix> = args.iter().map(|x| as_matrix(x).cloned()).collect::<Option<_>>()?;
+
+    match builtin.call(&values) {
+        Ok(result) => {
+            *node = matrix_node(result).with_span(node.span);
+            None
+        }
+        Err(err) => Some(ResolveMessage::error(&err).with_span(node.span))
+    }
+}
+
+/// Reads a number, unit or quantity node as a quantity
+pub fn as_quantity(node: &ASTNode) -> Option<Quantity> {
+    match &node.node_type {
+        ASTNodeType::Delimeter(Token::Number(num)) => Some(Quantity::number(*num)),
+        ASTNodeType::Delimeter(Token::Decimal(num)) => Some(Quantity::number(num.to_f64())),
+        ASTNodeType::Unit(unit) => Some(Quantity::from_unit(unit)),
+        ASTNodeType::Quantity(quantity) => Some(*quantity),
+        ASTNodeType::Rational(rational) => Some(Quantity::number(rational.to_f64())),
+        ASTNodeType::Conversion => as_quantity(&node.children[0]),
+        _ => None
+    }
+}
+
+/// Returns the target unit of a conversion node
+fn conversion_unit(node: &ASTNode) -> Option<Unit> {
+    match (&node.node_type, node.children.get(1).map(|x| &x.node_type)) {
+        (ASTNodeType::Conversion, Some(ASTNodeType::Unit(unit))) => Some(unit.clone()),
+        _ => None
+    }
+}
+
+/// Formats a resolved value or list for output, respecting unit conversions
+pub fn format_value(node: &ASTNode) -> Option<String> {
+    let unit = conversion_unit(node);
+    let format = |quantity: Quantity| match &unit {
+        Some(unit) => unit.format(quantity),
+        None => quantity.to_string()
+    };
+    let value = if node.node_type == ASTNodeType::Conversion { &node.children[0] } else { node };
+    match list_values(value) {
+        Some(values) => Some(values.into_iter().map(format).collect::<Vec<String>>().join(", ")),
+        None => as_quantity(node).map(format)
+    }
+}
+
+/// Formats the solution of an equation, in the unit it was converted to if there is one
+/// The namespace element of a solution, a fraction if it is exact
+fn solution_element(value: Quantity, exact: &Option<Rational>) -> NamespaceElement {
+    match exact {
+        Some(exact) => NamespaceElement::Rational(exact.clone()),
+        None => NamespaceElement::from_quantity(value),
+    }
+}
+
+fn format_solution(value: Quantity, display_unit: &Option<Unit>) -> String {
+    match display_unit {
+        Some(unit) if unit.dimension == value.dimension => unit.format(value),
+        _ => value.to_string()
+    }
+}
+
+/// Evaluates a logical operation on booleans, operations with unknowns are left as they are
+fn resolve_logic(node: &mut ASTNode, logic: Logic) -> Option<ResolveMessage> {
+    let mut operands = vec![];
+    for child in &node.children {
+        match child.node_type {
+            ASTNodeType::Delimeter(Token::Boolean(value)) => operands.push(value),
+            ASTNodeType::Delimeter(Token::Number(_) | Token::Decimal(_))
+            | ASTNodeType::Quantity(_) | ASTNodeType::Rational(_) | ASTNodeType::Complex(_) | ASTNodeType::Matrix(_) => {
+                return Some(ResolveMessage::error(&format!("{} expects true or false", logic.name())).with_span(child.span));
+            }
+            _ => return None,
+        }
+    }
+    let result = match operands.as_slice() {
+        [a] => logic.apply(*a, *a),
+        [a, b] => logic.apply(*a, *b),
+        _ => return None,
+    };
+    *node = ASTNode::delimeter(Token::Boolean(result)).with_span(node.span);
+    None
+}
+
+/// Checks that the value of a conversion can be expressed in the target unit
+fn resolve_conversion(node: &ASTNode) -> Option<ResolveMessage> {
+    // Every element of a list is converted
+    let values = list_values(&node.children[0]).or_else(|| as_quantity(&node.children[0]).map(|x| vec![x]));
+    match (values, &node.children[1].node_type) {
+        (Some(values), ASTNodeType::Unit(unit)) => values.iter().find(|x| x.dimension != unit.dimension).map(|quantity| {
+            ResolveMessage::error(&format!("Cannot convert [{}] to [{}]", quantity.dimension, unit.dimension)).with_span(node.span)
+        }),
+        (_, ASTNodeType::Unit(_)) => None,
+        _ => Some(ResolveMessage::error("Values can only be converted to a unit").with_span(node.children[1].span))
+    }
+}
+
+/// Creates a node from a complex number, real numbers become plain numbers
+pub fn complex_node(complex: Complex) -> ASTNode {
+    let complex = complex.clean();
+    if complex.is_real() {
+        ASTNode::number(complex.re)
+    } else {
+        ASTNode::new(ASTNodeType::Complex(complex), vec![])
+    }
+}
+
+/// Creates a node from a matrix, a matrix with a single element is a number
+pub fn matrix_node(matrix: Matrix) -> ASTNode {
+    if (matrix.rows(), matrix.cols()) == (1, 1) {
+        ASTNode::number(matrix.get(0, 0))
+    } else {
+        ASTNode::new(ASTNodeType::Matrix(matrix), vec![])
+    }
+}
+
+fn as_matrix(node: &ASTNode) -> Option<&Matrix> {
+    match &node.node_type {
+        ASTNodeType::Matrix(matrix) => Some(matrix),
+        _ => None,
+    }
+}
+
+/// Builds a vector from a vector literal, or a matrix if its elements are vectors which are the rows
+fn resolve_vector(node: &mut ASTNode) -> Option<ResolveMessage> {
+    let error = |message: &str| Some(ResolveMessage::error(message).with_span(node.span));
+    if node.children.iter().any(|x| matches!(x.node_type, ASTNodeType::Complex(_))) {
+        return error("Matrix elements must be real numbers");
+    }
+    // Some elements are unknown
+    if node.children.iter().any(|x| as_quantity(x).is_none() && as_matrix(x).is_none()) {
+        return None;
+    }
+    let elements: Option<Vec<Quantity>> = node.children.iter().map(as_quantity).collect();
+    let rows: Option<Vec<&Matrix>> = node.children.iter().map(as_matrix).collect();
+    let matrix = match (elements, rows) {
+        (Some(elements), _) if elements.iter().any(|x| !x.dimension.is_dimensionless()) => return error("Matrix elements cannot have units"),
+        (Some(elements), _) => Matrix::vector(elements.iter().map(|x| x.value).collect()),
+        (_, Some(rows)) if rows.iter().any(|x| x.cols() != 1) => return error("Matrix elements must be numbers"),
+        (_, Some(rows)) => match Matrix::from_rows(rows.iter().map(|x| x.to_rows().concat()).collect()) {
+            Some(matrix) => matrix,
+            None => return error("Matrix rows must have the same length"),
+        },
+        // A number next to vectors is a row with one element
+        _ => return error("Matrix rows must have the same length"),
+    };
+    *node = ASTNode::new(ASTNodeType::Matrix(matrix), vec![]).with_span(node.span);
+    None
+}
+
+/// Operand of an arithmetic operation with matrices
+enum MatrixOperand<'a> {
+    Matrix(&'a Matrix),
+    Number(f64),
+}
+
+/// Reads a matrix or a dimensionless number, `None` if the operand is unknown
+fn matrix_operand(node: &ASTNode) -> Option<Result<MatrixOperand<'_>, ResolveMessage>> {
+    match (as_matrix(node), as_quantity(node), &node.node_type) {
+        (Some(matrix), _, _) => Some(Ok(MatrixOperand::Matrix(matrix))),
+        (_, Some(quantity), _) if quantity.dimension.is_dimensionless() => Some(Ok(MatrixOperand::Number(quantity.value))),
+        (_, Some(_), _) | (_, _, ASTNodeType::Complex(_)) => Some(Err(ResolveMessage::error("Matrix elements must be dimensionless real numbers").with_span(node.span))),
+        _ => None,
+    }
+}
+
+/// Performs an arithmetic operation with matrices, numbers multiply and divide every element.
+/// Returns `None` if there are no matrices or some operand is unknown.
+fn resolve_matrices(node: &ASTNode) -> Option<Result<ASTNode, ResolveMessage>> {
+    if node.children.len() != 2 {
+        return None;
+    }
+    let (a, b) = (&node.children[0], &node.children[1]);
+    if as_matrix(a).is_none() && as_matrix(b).is_none() {
+        return None;
+    }
+    let error = |message: String| Some(Err(ResolveMessage::error(&message).with_span(node.span)));
+    let (a, b) = match (matrix_operand(a)?, matrix_operand(b)?) {
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(err), _) | (_, Err(err)) => return Some(Err(err)),
+    };
+    let result = match (&node.node_type, a, b) {
+        (ASTNodeType::Sum, MatrixOperand::Matrix(a), MatrixOperand::Matrix(b)) => match a.zip_with(b, |a, b| a + b) {
+            Some(result) => result,
+            None => return error(format!("Cannot add matrices of size {} and {}", a.size(), b.size())),
+        },
+        (ASTNodeType::Difference, MatrixOperand::Matrix(a), MatrixOperand::Matrix(b)) => match a.zip_with(b, |a, b| a - b) {
+            Some(result) => result,
+            None => return error(format!("Cannot subtract matrices of size {} and {}", a.size(), b.size())),
+        },
+        (ASTNodeType::Sum | ASTNodeType::Difference, _, _) => return error("Cannot add a number to a matrix".to_string()),
+        (ASTNodeType::Product, MatrixOperand::Matrix(a), MatrixOperand::Matrix(b)) => match a.checked_mul(b) {
+            Some(result) => result,
+            None => return error(format!("Cannot multiply matrices of size {} and {}", a.size(), b.size())),
+        },
+        (ASTNodeType::Product, MatrixOperand::Matrix(a), MatrixOperand::Number(b)) | (ASTNodeType::Product, MatrixOperand::Number(b), MatrixOperand::Matrix(a)) => a.map(|x| x * b),
+        (ASTNodeType::Quotient, MatrixOperand::Matrix(_), MatrixOperand::Number(0.)) => return error("Divide by zero".to_string()),
+        (ASTNodeType::Quotient, MatrixOperand::Matrix(a), MatrixOperand::Number(b)) => a.map(|x| x / b),
+        (ASTNodeType::Quotient, _, MatrixOperand::Matrix(_)) => return error("Cannot divide by a matrix".to_string()),
+        (ASTNodeType::Power, MatrixOperand::Matrix(_), MatrixOperand::Number(b)) if b.fract() != 0. => return error(format!("Cannot raise a matrix to the power of {}", format_number(b))),
+        (ASTNodeType::Power, MatrixOperand::Matrix(a), MatrixOperand::Number(_)) if a.rows() != a.cols() => return error(format!("Cannot raise a matrix of size {} to a power", a.size())),
+        (ASTNodeType::Power, MatrixOperand::Matrix(a), MatrixOperand::Number(b)) => match a.checked_pow(b as i32) {
+            Some(result) => result,
+            None => return error("Matrix is not invertible".to_string()),
+        },
+        (ASTNodeType::Power, _, MatrixOperand::Matrix(_)) => return error("Cannot raise to the power of a matrix".to_string()),
+        _ => return None,
+    };
+    Some(Ok(matrix_node(result).with_span(node.span)))
+}
+
+/// Reads a complex or a dimensionless real value
+fn as_complex(node: &ASTNode) -> Option<Complex> {
+    match &node.node_type {
+        ASTNodeType::Complex(complex) => Some(*complex),
+        _ => as_quantity(node).filter(|x| x.dimension.is_dimensionless()).map(|x| Complex::real(x.value)),
+    }
+}
+
+/// Creates a node from a quantity, dimensionless quantities become plain numbers
+pub fn quantity_node(quantity: Quantity) -> ASTNode {
+    if quantity.dimension.is_dimensionless() {
+        ASTNode::number(quantity.value)
+    } else {
+        ASTNode::new(ASTNodeType::Quantity(quantity), vec![])
+    }
+}
+
+/// Creates a list node from its values
+pub fn list_node(values: &[Quantity]) -> ASTNode {
+    ASTNode::new(ASTNodeType::List, values.iter().map(|x| quantity_node(*x)).collect())
+}
+
+/// Reads the values of a list, `None` if it is not a list or some of its elements are unknown
+fn list_values(node: &ASTNode) -> Option<Vec<Quantity>> {
+    match node.node_type {
+        ASTNodeType::List => node.children.iter().map(as_quantity).collect(),
+        _ => None,
+    }
+}
+
+/// Performs an arithmetic operation with lists, element by element.
+/// A value is combined with every element, ex. prices * 1.23, and two lists of the same length pair up their elements.
+fn resolve_lists(node: &ASTNode) -> Option<Result<ASTNode, ResolveMessage>> {
+    let operate = quantity_operation(&node.node_type)?;
+    if node.children.len() != 2 {
+        return None;
+    }
+    let (a, b) = (&node.children[0], &node.children[1]);
+    let pairs: Vec<(Quantity, Quantity)> = match (list_values(a), list_values(b)) {
+        (Some(_), None) | (None, Some(_)) if as_matrix(a).or(as_matrix(b)).is_some() => {
+            return Some(Err(ResolveMessage::error("Lists cannot be combined with matrices").with_span(node.span)));
+        }
+        (Some(a), Some(b)) if a.len() != b.len() => {
+            let message = format!("Cannot combine lists of length {} and {}", a.len(), b.len());
+            return Some(Err(ResolveMessage::error(&message).with_span(node.span)));
+        }
+        (Some(a), Some(b)) => a.into_iter().zip(b).collect(),
+        (Some(a), None) => {
+            let b = as_quantity(b)?;
+            a.into_iter().map(|x| (x, b)).collect()
+        }
+        (None, Some(b)) => {
+            let a = as_quantity(a)?;
+            b.into_iter().map(|x| (a, x)).collect()
+        }
+        (None, None) => return None,
+    };
+    let values: Result<Vec<Quantity>, ResolveMessage> = pairs.into_iter().map(|(a, b)| operate(a, b)).collect();
+    Some(match values {
+        Ok(values) => Ok(list_node(&values).with_span(node.span)),
+        Err(err) => Err(err.with_span(node.span)),
+    })
+}
+
+/// Arithmetic on two quantities
+type QuantityOperation = fn(Quantity, Quantity) -> Result<Quantity, ResolveMessage>;
+
+/// Returns the arithmetic of an operation node, `None` for other nodes
+fn quantity_operation(node_type: &ASTNodeType) -> Option<QuantityOperation> {
+    match node_type {
+        ASTNodeType::Sum => Some(|a, b| a.checked_add(b).map_err(|err| ResolveMessage::error(&err))),
+        ASTNodeType::Difference => Some(|a, b| a.checked_sub(b).map_err(|err| ResolveMessage::error(&err))),
+        ASTNodeType::Product => Some(|a, b| Ok(a * b)),
+        ASTNodeType::Quotient => Some(|a, b| if b.value != 0. { Ok(a / b) } else { Err(ResolveMessage::error("Divide by zero")) }), // TODO: Drilldown
+        ASTNodeType::Power => Some(|a, b| a.checked_pow(b).map_err(|err| ResolveMessage::error(&err))),
+        _ => None,
+    }
 }
 
 // this seems like a bad idea
 // TODO: replace this ASAP, as we want to be able to do a more generous match
-/// Performs an arithmetic operation on the children of `node`, replacing `node` with the result
-fn resolve_numbers(node: &mut ASTNode, operate: fn(f64, f64) -> Result<f64, ResolveMessage>) -> Option<ResolveMessage> {
+/// Performs an arithmetic operation on the children of `node`, replacing `node` with the result.
+/// Numbers and units are operated on as quantities.
+fn resolve_numbers(node: &mut ASTNode, operate: fn(Quantity, Quantity) -> Result<Quantity, ResolveMessage>) -> Option<ResolveMessage> {
     if node.children.len() == 2 {
-        match (&node.children[0].node_type, &node.children[1].node_type) {
-            (ASTNodeType::Delimeter(Token::Number(a)), ASTNodeType::Delimeter(Token::Number(b))) => {
-                let result = operate(*a, *b);
-                if let Ok(num) = result {
-                    *node = ASTNode::number(num);
-                } else {
-                    return Some(result.unwrap_err());
-                }
+        if let (Some(a), Some(b)) = (as_quantity(&node.children[0]), as_quantity(&node.children[1])) {
+            match operate(a, b) {
+                Ok(result) => *node = quantity_node(result).with_span(node.span),
+                Err(err) => return Some(err.with_span(node.span)),
             }
-            _ => ()
         }
         None
     } else {
@@ -555,15 +1801,112 @@ fn resolve_numbers(node: &mut ASTNode, operate: fn(f64, f64) -> Result<f64, Reso
     }
 }
 
+/// Performs an arithmetic operation exactly if both operands are exact.
+/// Returns `None` if the operation cannot be done exactly, ex. for 2^(1/2).
+fn resolve_rationals(node: &ASTNode) -> Option<Result<ASTNode, ResolveMessage>> {
+    let (a, b) = match (node.children.first().map(|x| &x.node_type), node.children.get(1).map(|x| &x.node_type)) {
+        (Some(ASTNodeType::Rational(a)), Some(ASTNodeType::Rational(b))) if node.children.len() == 2 => (a, b),
+        _ => return None,
+    };
+    let result = match node.node_type {
+        ASTNodeType::Sum => Some(a + b),
+        ASTNodeType::Difference => Some(a - b),
+        ASTNodeType::Product => Some(a * b),
+        ASTNodeType::Quotient => a.checked_div(b),
+        ASTNodeType::Power => {
+            let exp: i32 = b.numer().to_string().parse().ok().filter(|_| b.is_integer())?;
+            if (a.numer().digits() + a.denom().digits()).saturating_mul(exp.unsigned_abs() as usize) > MAX_EXACT_POWER_DIGITS {
+                return power_out_of_range(a.to_f64(), exp, node);
+            }
+            a.checked_pow(exp)
+        }
+        _ => return None,
+    };
+    Some(match result {
+        Some(result) => Ok(ASTNode::new(ASTNodeType::Rational(result), vec![]).with_span(node.span)),
+        None => Err(ResolveMessage::error("Divide by zero").with_span(node.span)),
+    })
+}
+
+/// A power with too many digits to be calculated exactly is calculated with floats,
+/// unless it is too small or too large for a float, ex. (1/3)^100000, which is an error instead of 0
+fn power_out_of_range(base: f64, exp: i32, node: &ASTNode) -> Option<Result<ASTNode, ResolveMessage>> {
+    let value = base.powi(exp);
+    let size = if value == 0. {
+        "small"
+    } else if value.is_infinite() {
+        "large"
+    } else {
+        return None;
+    };
+    Some(Err(ResolveMessage::error(&format!("The power is too {} to be calculated, it has more than {} exact digits", size, MAX_EXACT_POWER_DIGITS)).with_span(node.span)))
+}
+
+/// Performs an arithmetic operation with complex numbers, or a power of a negative number with a fractional exponent.
+/// Returns `None` if the operation is on real numbers only or some operand is unknown.
+fn resolve_complex(node: &ASTNode) -> Option<Result<ASTNode, ResolveMessage>> {
+    if node.children.len() != 2 {
+        return None;
+    }
+    let is_complex = |x: &ASTNode| matches!(x.node_type, ASTNodeType::Complex(_));
+    let (a, b) = (&node.children[0], &node.children[1]);
+    let negative_root = node.node_type == ASTNodeType::Power
+        && matches!((as_complex(a), as_complex(b)), (Some(a), Some(b)) if a.re < 0. && b.re.fract() != 0.);
+    if !is_complex(a) && !is_complex(b) && !negative_root {
+        return None;
+    }
+    if [a, b].iter().any(|x| as_quantity(x).is_some_and(|x| !x.dimension.is_dimensionless())) {
+        return Some(Err(ResolveMessage::error("Complex numbers cannot have units").with_span(node.span)));
+    }
+    let (a, b) = (as_complex(a)?, as_complex(b)?);
+    let result = match node.node_type {
+        ASTNodeType::Sum => a + b,
+        ASTNodeType::Difference => a - b,
+        ASTNodeType::Product => a * b,
+        ASTNodeType::Quotient if b == Complex::default() => return Some(Err(ResolveMessage::error("Divide by zero").with_span(node.span))),
+        ASTNodeType::Quotient => a / b,
+        ASTNodeType::Power => a.pow(b),
+        _ => return None,
+    };
+    Some(Ok(complex_node(result).with_span(node.span)))
+}
+
+/// Performs an arithmetic operation on decimals, rounding inexact results to `precision` significant digits.
+/// Returns `None` if the operands are not decimals or the power is not an integer.
+fn resolve_decimals(node: &ASTNode, precision: usize) -> Option<Result<ASTNode, ResolveMessage>> {
+    let (a, b) = match (node.children.first().map(|x| &x.node_type), node.children.get(1).map(|x| &x.node_type)) {
+        (Some(ASTNodeType::Delimeter(Token::Decimal(a))), Some(ASTNodeType::Delimeter(Token::Decimal(b)))) if node.children.len() == 2 => (a, b),
+        _ => return None,
+    };
+    let result = match node.node_type {
+        ASTNodeType::Sum => Some(a + b),
+        ASTNodeType::Difference => Some(a - b),
+        ASTNodeType::Product => Some(a * b),
+        ASTNodeType::Quotient => a.checked_div(b, precision),
+        ASTNodeType::Power => {
+            let exp: i32 = b.to_string().parse().ok()?;
+            if a.mantissa().digits().saturating_mul(exp.unsigned_abs() as usize) > MAX_EXACT_POWER_DIGITS {
+                return power_out_of_range(a.to_f64(), exp, node);
+            }
+            a.checked_pow(exp, precision)
+        }
+        _ => return None,
+    };
+    Some(match result {
+        Some(result) => Ok(ASTNode::delimeter(Token::Decimal(result)).with_span(node.span)),
+        None => Err(ResolveMessage::error("Divide by zero").with_span(node.span)),
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{parser::node::{ASTNode, ASTNodeType}, resolver::{self, NamespaceElement, ResolveMessage, Resolver}, tokenizer::Token};
+    use crate::{parser::node::{ASTNode, ASTNodeType}, resolver::{self, NamespaceElement, ResolveMessage, Resolver, UserFunction}, tokenizer::Token, units::{Dimension, Quantity}};
 
     #[test]
     fn resolve_numbers() {
         let mut node = ASTNode::new(ASTNodeType::Sum, vec![ASTNode::number(10.), ASTNode::number(2.)]);
 
-        let output = resolver::resolve_numbers(&mut node, |a, b| Ok(a + b));
+        let output = resolver::resolve_numbers(&mut node, |a, b| a.checked_add(b).map_err(|err| ResolveMessage::error(&err)));
 
         assert!(output.is_none());
 
@@ -684,6 +2027,30 @@ mod tests {
         assert_eq!(output.first().unwrap().content, "y = 0.02");
     }
 
+    #[test]
+    fn resolve_constants_are_read_only() {
+        let node = ASTNode::new(ASTNodeType::Equality, vec![
+            ASTNode::delimeter(Token::Name("pi".into())),
+            ASTNode::number(3.)
+        ]);
+
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve_line(node);
+
+        assert_eq!(output, vec![ResolveMessage::error("Cannot reassign constant pi")]);
+        assert_eq!(resolver.namespace.get("pi"), Some(&NamespaceElement::Number(std::f64::consts::PI)));
+    }
+
+    #[test]
+    fn resolve_physics_constants_are_opt_in() {
+        assert_eq!(Resolver::new().namespace.get("c"), None);
+        assert_eq!(
+            Resolver::new().with_physics_constants().namespace.get("c"),
+            Some(&NamespaceElement::Quantity(Quantity::new(299_792_458., Dimension([1, 0, -1, 0, 0, 0, 0]))))
+        );
+    }
+
     #[test]
     fn resolve_inserts_into_namespace() {
         let node = ASTNode::new(ASTNodeType::Equality, vec![
@@ -701,7 +2068,7 @@ mod tests {
 
         assert_eq!(output.first().unwrap().content, "guacamole = 100");
 
-        assert_eq!(resolver.namespace.get("guacamole".into()), Some(&NamespaceElement::Number(100.)));
+        assert_eq!(resolver.namespace.get("guacamole"), Some(&NamespaceElement::Number(100.)));
     }
 
     #[test]
@@ -729,11 +2096,14 @@ mod tests {
         assert_eq!(output.first().unwrap().content, "fn(x) = [...]");
 
         assert_eq!(
-            resolver.namespace.get("fn".into()),
-            Some(&NamespaceElement::Function(ASTNode::new(ASTNodeType::Sum, vec![
-                ASTNode::new(ASTNodeType::FnArgument(0), vec![]),
-                ASTNode::number(10.),
-            ])))
+            resolver.namespace.get("fn"),
+            Some(&NamespaceElement::Function(UserFunction {
+                params: vec!["x".into()],
+                body: ASTNode::new(ASTNodeType::Sum, vec![
+                    ASTNode::new(ASTNodeType::FnArgument(0), vec![]),
+                    ASTNode::number(10.),
+                ]),
+            }))
         );
     }
 
@@ -741,12 +2111,13 @@ mod tests {
     fn resolve_fn_works_with_one_arg() {
         let mut resolver = Resolver::new();
 
-        resolver.namespace.insert(String::from("f"), NamespaceElement::Function(
-            ASTNode::new(ASTNodeType::Sum, vec![
+        resolver.namespace.insert(String::from("f"), NamespaceElement::Function(UserFunction {
+            params: vec!["x".into()],
+            body: ASTNode::new(ASTNodeType::Sum, vec![
                 ASTNode::new(ASTNodeType::FnArgument(0), vec![]),
                 ASTNode::number(4.)
             ]),
-        ));
+        }));
 
         let node = ASTNode::new(ASTNodeType::Function("f".into()), vec![
             ASTNode::number(10.),
@@ -765,19 +2136,21 @@ mod tests {
     fn resolve_fn_works_with_composition() {
         let mut resolver = Resolver::new();
 
-        resolver.namespace.insert(String::from("f"), NamespaceElement::Function(
-            ASTNode::new(ASTNodeType::Sum, vec![
+        resolver.namespace.insert(String::from("f"), NamespaceElement::Function(UserFunction {
+            params: vec!["x".into()],
+            body: ASTNode::new(ASTNodeType::Sum, vec![
                 ASTNode::new(ASTNodeType::FnArgument(0), vec![]),
                 ASTNode::number(4.)
             ]),
-        ));
+        }));
 
-        resolver.namespace.insert(String::from("g"), NamespaceElement::Function(
-            ASTNode::new(ASTNodeType::Power, vec![
+        resolver.namespace.insert(String::from("g"), NamespaceElement::Function(UserFunction {
+            params: vec!["x".into()],
+            body: ASTNode::new(ASTNodeType::Power, vec![
                 ASTNode::new(ASTNodeType::FnArgument(0), vec![]),
                 ASTNode::number(2.)
             ]),
-        ));
+        }));
 
         let node = ASTNode::new(ASTNodeType::Function("f".into()), vec![
             ASTNode::new(ASTNodeType::Function("g".into()), vec![
@@ -0,0 +1,306 @@
+use crate::{number::{complex::Complex, matrix::Matrix}, units::Quantity};
+
+/// Number of arguments a builtin function accepts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arity {
+    Exact(usize),
+    Between(usize, usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    pub fn accepts(&self, n: usize) -> bool {
+        match *self {
+            Arity::Exact(x) => n == x,
+            Arity::Between(min, max) => n >= min && n <= max,
+            Arity::AtLeast(min) => n >= min,
+        }
+    }
+
+    /// Describes the accepted number of arguments, ex. 2 arguments
+    pub fn describe(&self) -> String {
+        let plural = |n: usize| if n == 1 { "argument" } else { "arguments" };
+        match *self {
+            Arity::Exact(x) => format!("{} {}", x, plural(x)),
+            Arity::Between(min, max) => format!("{} to {} arguments", min, max),
+            Arity::AtLeast(min) => format!("at least {} {}", min, plural(min)),
+        }
+    }
+}
+
+/// A function implemented natively, available in every namespace unless shadowed by a user function
+pub struct Builtin {
+    pub name: &'static str,
+    pub arity: Arity,
+    eval: fn(&[Quantity]) -> Result<Quantity, String>,
+}
+
+impl Builtin {
+    /// Checks the argument count and evaluates the function
+    pub fn call(&self, args: &[Quantity]) -> Result<Quantity, String> {
+        if !self.arity.accepts(args.len()) {
+            return Err(format!("{} expects {}, got {}", self.name, self.arity.describe(), args.len()));
+        }
+        let result = (self.eval)(args)?;
+        // Results which are not a number mean the arguments are out of the domain, ex. sqrt(-1) or ln(0)
+        if !result.value.is_finite() && args.iter().all(|x| x.value.is_finite()) {
+            let args: Vec<String> = args.iter().map(|x| x.to_string()).collect();
+            return Err(format!("{}({}) is undefined", self.name, args.join(", ")));
+        }
+        Ok(result)
+    }
+}
+
+static BUILTINS: &[Builtin] = &[
+    // Trigonometry
+    Builtin { name: "sin", arity: Arity::Exact(1), eval: |x| real("sin", x[0], f64::sin) },
+    Builtin { name: "cos", arity: Arity::Exact(1), eval: |x| real("cos", x[0], f64::cos) },
+    Builtin { name: "tan", arity: Arity::Exact(1), eval: |x| real("tan", x[0], f64::tan) },
+    Builtin { name: "asin", arity: Arity::Exact(1), eval: |x| real("asin", x[0], f64::asin) },
+    Builtin { name: "acos", arity: Arity::Exact(1), eval: |x| real("acos", x[0], f64::acos) },
+    Builtin { name: "atan", arity: Arity::Exact(1), eval: |x| real("atan", x[0], f64::atan) },
+    Builtin { name: "atan2", arity: Arity::Exact(2), eval: |x| same_dimension("atan2", x).map(|_| Quantity::number(f64::atan2(x[0].value, x[1].value))) },
+    // Hyperbolic
+    Builtin { name: "sinh", arity: Arity::Exact(1), eval: |x| real("sinh", x[0], f64::sinh) },
+    Builtin { name: "cosh", arity: Arity::Exact(1), eval: |x| real("cosh", x[0], f64::cosh) },
+    Builtin { name: "tanh", arity: Arity::Exact(1), eval: |x| real("tanh", x[0], f64::tanh) },
+    Builtin { name: "asinh", arity: Arity::Exact(1), eval: |x| real("asinh", x[0], f64::asinh) },
+    Builtin { name: "acosh", arity: Arity::Exact(1), eval: |x| real("acosh", x[0], f64::acosh) },
+    Builtin { name: "atanh", arity: Arity::Exact(1), eval: |x| real("atanh", x[0], f64::atanh) },
+    // Exponents and logarithms
+    Builtin { name: "exp", arity: Arity::Exact(1), eval: |x| real("exp", x[0], f64::exp) },
+    Builtin { name: "ln", arity: Arity::Exact(1), eval: |x| real("ln", x[0], f64::ln) },
+    Builtin { name: "log", arity: Arity::Between(1, 2), eval: log },
+    Builtin { name: "log2", arity: Arity::Exact(1), eval: |x| real("log2", x[0], f64::log2) },
+    Builtin { name: "log10", arity: Arity::Exact(1), eval: |x| real("log10", x[0], f64::log10) },
+    // Roots keep track of units, sqrt([m^2]) is [m]
+    Builtin { name: "sqrt", arity: Arity::Exact(1), eval: |x| x[0].checked_pow(Quantity::number(0.5)) },
+    Builtin { name: "cbrt", arity: Arity::Exact(1), eval: |x| root3(x[0]) },
+    // Rounding and comparison, these work on any unit
+    Builtin { name: "abs", arity: Arity::Exact(1), eval: |x| Ok(Quantity::new(x[0].value.abs(), x[0].dimension)) },
+    Builtin { name: "floor", arity: Arity::Exact(1), eval: |x| Ok(Quantity::new(x[0].value.floor(), x[0].dimension)) },
+    Builtin { name: "ceil", arity: Arity::Exact(1), eval: |x| Ok(Quantity::new(x[0].value.ceil(), x[0].dimension)) },
+    Builtin { name: "round", arity: Arity::Exact(1), eval: |x| Ok(Quantity::new(x[0].value.round(), x[0].dimension)) },
+    // Real numbers lie on the real axis, the angle of negative ones is pi
+    Builtin { name: "arg", arity: Arity::Exact(1), eval: |x| Ok(Quantity::number(if x[0].value < 0. { std::f64::consts::PI } else { 0. })) },
+    Builtin { name: "conj", arity: Arity::Exact(1), eval: |x| Ok(x[0]) },
+    Builtin { name: "min", arity: Arity::AtLeast(1), eval: |x| extremum("min", x, f64::min) },
+    Builtin { name: "max", arity: Arity::AtLeast(1), eval: |x| extremum("max", x, f64::max) },
+    // Aggregates, a list passed as the argument is spread into the arguments, ex. mean(prices)
+    Builtin { name: "sum", arity: Arity::AtLeast(1), eval: |x| same_dimension("sum", x).map(|_| total(x)) },
+    Builtin { name: "mean", arity: Arity::AtLeast(1), eval: |x| same_dimension("mean", x).map(|_| mean(x)) },
+    Builtin { name: "median", arity: Arity::AtLeast(1), eval: median },
+    Builtin { name: "stdev", arity: Arity::AtLeast(2), eval: stdev },
+    Builtin { name: "len", arity: Arity::AtLeast(1), eval: |x| Ok(Quantity::number(x.len() as f64)) },
+];
+
+/// Function of a complex argument
+pub type ComplexFn = fn(Complex) -> Complex;
+
+/// Variants of builtins for complex arguments, also used when the real function is undefined, ex. for sqrt(-1)
+static COMPLEX_BUILTINS: &[(&str, ComplexFn)] = &[
+    ("sqrt", Complex::sqrt),
+    ("exp", Complex::exp),
+    ("ln", Complex::ln),
+    ("abs", |x| Complex::real(x.abs())),
+    ("arg", |x| Complex::real(x.arg())),
+    ("conj", Complex::conj),
+];
+
+/// A builtin function of matrices and vectors, results with a single element are numbers
+pub struct MatrixBuiltin {
+    pub name: &'static str,
+    pub arity: Arity,
+    eval: fn(&[Matrix]) -> Result<Matrix, String>,
+}
+
+impl MatrixBuiltin {
+    /// Checks the argument count and evaluates the function
+    pub fn call(&self, args: &[Matrix]) -> Result<Matrix, String> {
+        if !self.arity.accepts(args.len()) {
+            return Err(format!("{} expects {}, got {}", self.name, self.arity.describe(), args.len()));
+        }
+        (self.eval)(args)
+    }
+}
+
+static MATRIX_BUILTINS: &[MatrixBuiltin] = &[
+    MatrixBuiltin { name: "det", arity: Arity::Exact(1), eval: |x| x[0].det().map(|x| Matrix::vector(vec![x])).ok_or_else(|| square("det", &x[0])) },
+    MatrixBuiltin { name: "inv", arity: Arity::Exact(1), eval: inverse },
+    MatrixBuiltin { name: "transpose", arity: Arity::Exact(1), eval: |x| Ok(x[0].transpose()) },
+    MatrixBuiltin {
+        name: "dot",
+        arity: Arity::Exact(2),
+        eval: |x| x[0].dot(&x[1]).map(|x| Matrix::vector(vec![x])).ok_or_else(|| "dot expects two vectors of the same length".to_string()),
+    },
+    MatrixBuiltin {
+        name: "cross",
+        arity: Arity::Exact(2),
+        eval: |x| x[0].cross(&x[1]).ok_or_else(|| "cross expects two vectors of length 3".to_string()),
+    },
+];
+
+/// A builtin which transforms expressions rather than values, its result can contain unknowns
+pub struct SymbolicBuiltin {
+    pub name: &'static str,
+    pub arity: Arity,
+}
+
+impl SymbolicBuiltin {
+    pub fn check_arity(&self, n: usize) -> Result<(), String> {
+        if self.arity.accepts(n) {
+            Ok(())
+        } else {
+            Err(format!("{} expects {}, got {}", self.name, self.arity.describe(), n))
+        }
+    }
+}
+
+static SYMBOLIC_BUILTINS: &[SymbolicBuiltin] = &[
+    SymbolicBuiltin { name: "simplify", arity: Arity::Exact(1) },
+    SymbolicBuiltin { name: "diff", arity: Arity::Exact(2) },
+];
+
+/// Builtins which evaluate an expression over a bound variable, ex. sum(k^2, k, 1, 10)
+pub const BOUND_BUILTINS: &[&str] = &["integrate", "sum", "prod"];
+
+/// Finds a builtin function by name
+pub fn get_builtin(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|x| x.name == name)
+}
+
+/// Finds a builtin function of matrices by name
+pub fn get_matrix_builtin(name: &str) -> Option<&'static MatrixBuiltin> {
+    MATRIX_BUILTINS.iter().find(|x| x.name == name)
+}
+
+/// Finds a builtin which transforms expressions by name
+pub fn get_symbolic_builtin(name: &str) -> Option<&'static SymbolicBuiltin> {
+    SYMBOLIC_BUILTINS.iter().find(|x| x.name == name)
+}
+
+/// Finds the complex variant of a builtin function by name
+pub fn get_complex_builtin(name: &str) -> Option<ComplexFn> {
+    COMPLEX_BUILTINS.iter().find(|x| x.0 == name).map(|x| x.1)
+}
+
+/// Applies a function of a real number, which only makes sense for dimensionless values
+fn real<F: Fn(f64) -> f64>(name: &str, x: Quantity, f: F) -> Result<Quantity, String> {
+    if !x.dimension.is_dimensionless() {
+        return Err(format!("{} expects a dimensionless argument, got [{}]", name, x.dimension));
+    }
+    Ok(Quantity::number(f(x.value)))
+}
+
+fn same_dimension(name: &str, args: &[Quantity]) -> Result<(), String> {
+    match args.iter().find(|x| x.dimension != args[0].dimension) {
+        Some(other) => Err(format!("{} expects arguments of the same unit, got [{}] and [{}]", name, args[0].dimension, other.dimension)),
+        None => Ok(()),
+    }
+}
+
+/// Logarithm in base 10 or in the base given as the second argument
+fn log(args: &[Quantity]) -> Result<Quantity, String> {
+    match args.get(1) {
+        Some(base) => {
+            let base = real("log", *base, |x| x)?.value;
+            real("log", args[0], |x| x.log(base))
+        }
+        // log10 is exact for powers of ten, x.log(10.) is not
+        None => real("log", args[0], f64::log10),
+    }
+}
+
+fn root3(x: Quantity) -> Result<Quantity, String> {
+    // powf does not take roots of negative numbers
+    let result = x.checked_pow(Quantity::number(1. / 3.))?;
+    Ok(Quantity::new(x.value.cbrt(), result.dimension))
+}
+
+fn total(args: &[Quantity]) -> Quantity {
+    Quantity::new(args.iter().map(|x| x.value).sum(), args[0].dimension)
+}
+
+fn mean(args: &[Quantity]) -> Quantity {
+    Quantity::new(total(args).value / args.len() as f64, args[0].dimension)
+}
+
+/// Middle value, or the mean of the two middle values for an even number of values
+fn median(args: &[Quantity]) -> Result<Quantity, String> {
+    same_dimension("median", args)?;
+    let mut values: Vec<f64> = args.iter().map(|x| x.value).collect();
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    let value = if values.len().is_multiple_of(2) { (values[middle - 1] + values[middle]) / 2. } else { values[middle] };
+    Ok(Quantity::new(value, args[0].dimension))
+}
+
+/// Sample standard deviation, which is what spreadsheets calculate
+fn stdev(args: &[Quantity]) -> Result<Quantity, String> {
+    same_dimension("stdev", args)?;
+    let mean = mean(args).value;
+    let variance = args.iter().map(|x| (x.value - mean).powi(2)).sum::<f64>() / (args.len() - 1) as f64;
+    Ok(Quantity::new(variance.sqrt(), args[0].dimension))
+}
+
+fn square(name: &str, x: &Matrix) -> String {
+    format!("{} expects a square matrix, got {}", name, x.size())
+}
+
+fn inverse(args: &[Matrix]) -> Result<Matrix, String> {
+    if args[0].rows() != args[0].cols() {
+        return Err(square("inv", &args[0]));
+    }
+    args[0].inverse().ok_or_else(|| "Matrix is not invertible".to_string())
+}
+
+fn extremum(name: &str, args: &[Quantity], pick: fn(f64, f64) -> f64) -> Result<Quantity, String> {
+    same_dimension(name, args)?;
+    let value = args.iter().map(|x| x.value).fold(args[0].value, pick);
+    Ok(Quantity::new(value, args[0].dimension))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::units::{Dimension, Quantity};
+
+    use super::get_builtin;
+
+    fn call(name: &str, args: &[f64]) -> Result<Quantity, String> {
+        let args: Vec<Quantity> = args.iter().map(|x| Quantity::number(*x)).collect();
+        get_builtin(name).unwrap().call(&args)
+    }
+
+    #[test]
+    fn builtins_evaluate() {
+        assert_eq!(call("sqrt", &[16.]), Ok(Quantity::number(4.)));
+        assert_eq!(call("cbrt", &[-8.]), Ok(Quantity::number(-2.)));
+        assert_eq!(call("log", &[1000.]), Ok(Quantity::number(3.)));
+        assert_eq!(call("log", &[8., 2.]), Ok(Quantity::number(3.)));
+        assert_eq!(call("max", &[3., 10., -1.]), Ok(Quantity::number(10.)));
+        assert_eq!(call("round", &[2.5]), Ok(Quantity::number(3.)));
+        assert_eq!(call("median", &[3., 10., -1., 4.]), Ok(Quantity::number(3.5)));
+        assert_eq!(call("stdev", &[2., 4., 4., 4., 5., 5., 7., 9.]), Ok(Quantity::number(32f64.sqrt() / 7f64.sqrt())));
+    }
+
+    #[test]
+    fn builtins_check_arity() {
+        assert_eq!(call("sin", &[1., 2.]), Err("sin expects 1 argument, got 2".into()));
+        assert_eq!(call("log", &[]), Err("log expects 1 to 2 arguments, got 0".into()));
+        assert_eq!(call("min", &[]), Err("min expects at least 1 argument, got 0".into()));
+    }
+
+    #[test]
+    fn builtins_report_domain_errors() {
+        assert_eq!(call("sqrt", &[-1.]), Err("sqrt(-1) is undefined".into()));
+        assert_eq!(call("ln", &[0.]), Err("ln(0) is undefined".into()));
+        assert_eq!(call("acos", &[2.]), Err("acos(2) is undefined".into()));
+    }
+
+    #[test]
+    fn builtins_respect_units() {
+        let area = Quantity::new(9., Dimension([2, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(get_builtin("sqrt").unwrap().call(&[area]), Ok(Quantity::new(3., Dimension([1, 0, 0, 0, 0, 0, 0]))));
+        assert!(get_builtin("sin").unwrap().call(&[area]).is_err());
+    }
+}
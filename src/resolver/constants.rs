@@ -0,0 +1,27 @@
+use std::f64::consts;
+
+use crate::units::{Dimension, Quantity};
+
+/// Mathematical constants, defined in every resolver
+pub static MATH_CONSTANTS: &[(&str, f64)] = &[
+    ("pi", consts::PI),
+    ("e", consts::E),
+    ("tau", consts::TAU),
+];
+
+/// Physical constants in SI base units, only defined after calling `Resolver::with_physics_constants`
+pub static PHYSICS_CONSTANTS: &[(&str, f64, Dimension)] = &[
+    ("G", 6.6743e-11, Dimension([3, -1, -2, 0, 0, 0, 0])), // gravitational constant, m^3/(kg*s^2)
+    ("c", 299_792_458., Dimension([1, 0, -1, 0, 0, 0, 0])), // speed of light, m/s
+    ("h", 6.626_070_15e-34, Dimension([2, 1, -1, 0, 0, 0, 0])), // Planck constant, J*s
+    ("k_B", 1.380_649e-23, Dimension([2, 1, -2, 0, -1, 0, 0])), // Boltzmann constant, J/K
+    ("N_A", 6.022_140_76e23, Dimension([0, 0, 0, 0, 0, -1, 0])), // Avogadro constant, 1/mol
+];
+
+pub fn math_constants() -> impl Iterator<Item = (&'static str, Quantity)> {
+    MATH_CONSTANTS.iter().map(|(name, value)| (*name, Quantity::number(*value)))
+}
+
+pub fn physics_constants() -> impl Iterator<Item = (&'static str, Quantity)> {
+    PHYSICS_CONSTANTS.iter().map(|(name, value, dimension)| (*name, Quantity::new(*value, *dimension)))
+}
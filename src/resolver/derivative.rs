@@ -0,0 +1,139 @@
+use crate::parser::node::{ASTNode, ASTNodeType};
+
+use super::{list_node_to_vec, simplify::simplify};
+
+/// Differentiates an expression with respect to a variable, ex. x^2 + sin(x) to 2 x + cos(x).
+/// The variable is usually a name, but it can be any node, ex. an argument of a function body.
+pub fn differentiate(node: &ASTNode, variable: &ASTNode) -> Result<ASTNode, String> {
+    derivative(node, variable).map(|x| simplify(&x))
+}
+
+fn derivative(node: &ASTNode, variable: &ASTNode) -> Result<ASTNode, String> {
+    if node == variable {
+        return Ok(ASTNode::number(1.));
+    }
+    if !contains(node, variable) {
+        return Ok(ASTNode::number(0.));
+    }
+    let d = |x: &ASTNode| derivative(x, variable);
+    match (&node.node_type, node.children.as_slice()) {
+        (ASTNodeType::Sum, [a, b]) => Ok(operation(ASTNodeType::Sum, d(a)?, d(b)?)),
+        (ASTNodeType::Difference, [a, b]) => Ok(operation(ASTNodeType::Difference, d(a)?, d(b)?)),
+        // (a b)' = a' b + a b'
+        (ASTNodeType::Product, [a, b]) => Ok(operation(
+            ASTNodeType::Sum,
+            operation(ASTNodeType::Product, d(a)?, b.clone()),
+            operation(ASTNodeType::Product, a.clone(), d(b)?),
+        )),
+        // (a / b)' = (a' b - a b') / b^2
+        (ASTNodeType::Quotient, [a, b]) => Ok(operation(
+            ASTNodeType::Quotient,
+            operation(
+                ASTNodeType::Difference,
+                operation(ASTNodeType::Product, d(a)?, b.clone()),
+                operation(ASTNodeType::Product, a.clone(), d(b)?),
+            ),
+            operation(ASTNodeType::Power, b.clone(), ASTNode::number(2.)),
+        )),
+        // (a^n)' = n a^(n - 1) a'
+        (ASTNodeType::Power, [a, b]) if !contains(b, variable) => {
+            let exp = operation(ASTNodeType::Difference, b.clone(), ASTNode::number(1.));
+            let power = operation(ASTNodeType::Product, b.clone(), operation(ASTNodeType::Power, a.clone(), exp));
+            Ok(operation(ASTNodeType::Product, power, d(a)?))
+        }
+        // (a^b)' = a^b (b' ln(a) + b a' / a)
+        (ASTNodeType::Power, [a, b]) => {
+            let ln = operation(ASTNodeType::Product, d(b)?, call("ln", a.clone()));
+            let rest = operation(ASTNodeType::Quotient, operation(ASTNodeType::Product, b.clone(), d(a)?), a.clone());
+            Ok(operation(ASTNodeType::Product, node.clone(), operation(ASTNodeType::Sum, ln, rest)))
+        }
+        // Chain rule, f(u)' = f'(u) u'
+        (ASTNodeType::Function(name), [args]) => match list_node_to_vec(args).as_slice() {
+            [u] => {
+                let outer = outer_derivative(name, u).ok_or_else(|| format!("Cannot differentiate {}", name))?;
+                Ok(operation(ASTNodeType::Product, outer, d(u)?))
+            }
+            _ => Err(format!("Cannot differentiate {}", name)),
+        },
+        _ => Err(format!("Cannot differentiate {}", node)),
+    }
+}
+
+/// Derivative of a builtin function of one argument at u, ex. cos(u) for sin
+fn outer_derivative(name: &str, u: &ASTNode) -> Option<ASTNode> {
+    let one = || ASTNode::number(1.);
+    let square = || operation(ASTNodeType::Power, u.clone(), ASTNode::number(2.));
+    let reciprocal = |x: ASTNode| operation(ASTNodeType::Quotient, one(), x);
+    let negate = |x: ASTNode| operation(ASTNodeType::Product, ASTNode::number(-1.), x);
+    let ln_times_u = |base: f64| operation(ASTNodeType::Product, u.clone(), call("ln", ASTNode::number(base)));
+
+    Some(match name {
+        "sin" => call("cos", u.clone()),
+        "cos" => negate(call("sin", u.clone())),
+        "tan" => reciprocal(operation(ASTNodeType::Power, call("cos", u.clone()), ASTNode::number(2.))),
+        "asin" => reciprocal(call("sqrt", operation(ASTNodeType::Difference, one(), square()))),
+        "acos" => negate(reciprocal(call("sqrt", operation(ASTNodeType::Difference, one(), square())))),
+        "atan" => reciprocal(operation(ASTNodeType::Sum, one(), square())),
+        "sinh" => call("cosh", u.clone()),
+        "cosh" => call("sinh", u.clone()),
+        "tanh" => reciprocal(operation(ASTNodeType::Power, call("cosh", u.clone()), ASTNode::number(2.))),
+        "asinh" => reciprocal(call("sqrt", operation(ASTNodeType::Sum, square(), one()))),
+        "acosh" => reciprocal(call("sqrt", operation(ASTNodeType::Difference, square(), one()))),
+        "atanh" => reciprocal(operation(ASTNodeType::Difference, one(), square())),
+        "exp" => call("exp", u.clone()),
+        "ln" => reciprocal(u.clone()),
+        "log" | "log10" => reciprocal(ln_times_u(10.)),
+        "log2" => reciprocal(ln_times_u(2.)),
+        "sqrt" => reciprocal(operation(ASTNodeType::Product, ASTNode::number(2.), call("sqrt", u.clone()))),
+        "cbrt" => reciprocal(operation(
+            ASTNodeType::Product,
+            ASTNode::number(3.),
+            operation(ASTNodeType::Power, call("cbrt", u.clone()), ASTNode::number(2.)),
+        )),
+        "abs" => operation(ASTNodeType::Quotient, u.clone(), call("abs", u.clone())),
+        _ => return None,
+    })
+}
+
+fn contains(node: &ASTNode, variable: &ASTNode) -> bool {
+    node == variable || node.children.iter().any(|x| contains(x, variable))
+}
+
+fn operation(node_type: ASTNodeType, a: ASTNode, b: ASTNode) -> ASTNode {
+    ASTNode::new(node_type, vec![a, b])
+}
+
+fn call(name: &str, arg: ASTNode) -> ASTNode {
+    ASTNode::new(ASTNodeType::Function(name.into()), vec![arg])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::{node::ASTNode, parsers::parse}, tokenizer::{tokenize, Token}};
+
+    use super::differentiate;
+
+    fn derivative(expression: &str) -> Result<String, String> {
+        let node = parse(&tokenize(expression).unwrap()).unwrap();
+        differentiate(&node, &ASTNode::delimeter(Token::Name("x".into()))).map(|x| x.to_string())
+    }
+
+    #[test]
+    fn differentiate_operations() {
+        assert_eq!(derivative("3x^2 + 2x - 5"), Ok("6 x + 2".into()));
+        assert_eq!(derivative("x * y"), Ok("y".into()));
+        assert_eq!(derivative("1 / x"), Ok("-1 / x^2".into()));
+        assert_eq!(derivative("(x^3) / 3"), Ok("x^2".into()));
+        assert_eq!(derivative("2^x"), Ok("0.6931471805599453 * 2^x".into()));
+        assert_eq!(derivative("y^2"), Ok("0".into()));
+    }
+
+    #[test]
+    fn differentiate_functions() {
+        assert_eq!(derivative("sin(x)"), Ok("cos(x)".into()));
+        assert_eq!(derivative("cos(2x)"), Ok("-2 sin(2 x)".into()));
+        assert_eq!(derivative("exp(x^2)"), Ok("2 exp(x^2) * x".into()));
+        assert_eq!(derivative("ln(x)"), Ok("1 / x".into()));
+        assert_eq!(derivative("floor(x)"), Err("Cannot differentiate floor".into()));
+    }
+}
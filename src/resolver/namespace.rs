@@ -1,19 +1,70 @@
-use crate::parser::node::ASTNode;
+use crate::{number::{complex::Complex, decimal::Decimal, matrix::Matrix, rational::Rational}, parser::{node::{ASTNode, ASTNodeType}, walkers::post_order_mut}, resolver::{complex_node, list_node, matrix_node, quantity_node}, tokenizer::Token, units::Quantity};
 
 // TODO: New ASTNode type for values (data types) specifically and a system of operations between them
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum NamespaceElement {
     Number(f64),
-    // BigNum(f64),
-    // Matrix(f64),
-    Function(ASTNode)
+    Quantity(Quantity),
+    /// Exact value, only stored in rational mode
+    Rational(Rational),
+    /// Decimal with more digits than a float, only stored in decimal mode
+    BigNum(Decimal),
+    Complex(Complex),
+    Matrix(Matrix),
+    List(Vec<Quantity>),
+    Boolean(bool),
+    Function(UserFunction)
+}
+
+/// A function defined with let, ex. let f(x, y) = x * y
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserFunction {
+    pub params: Vec<String>,
+    /// Expression with the parameters replaced by `FnArgument`s, in the order of `params`
+    pub body: ASTNode,
+}
+
+impl UserFunction {
+    pub fn arity(&self) -> usize {
+        self.params.len()
+    }
+
+    /// The function as it would be declared, ex. f(x, y)
+    pub fn signature(&self, name: &str) -> String {
+        format!("{}({})", name, self.params.join(", "))
+    }
+
+    /// The body with the parameters written by their names
+    pub fn expression(&self) -> ASTNode {
+        let mut body = self.body.clone();
+        post_order_mut(&mut body, &mut |x| if let ASTNodeType::FnArgument(index) = x.node_type {
+            *x = ASTNode::delimeter(Token::Name(self.params[index].clone()));
+        });
+        body
+    }
 }
 
 impl NamespaceElement {
+    /// Stores dimensionless quantities as plain numbers
+    pub fn from_quantity(quantity: Quantity) -> Self {
+        if quantity.dimension.is_dimensionless() {
+            NamespaceElement::Number(quantity.value)
+        } else {
+            NamespaceElement::Quantity(quantity)
+        }
+    }
+
     pub fn as_astnode(&self) -> Option<ASTNode> {
         match self {
             NamespaceElement::Number(num) => Some(ASTNode::number(*num)),
+            NamespaceElement::Quantity(quantity) => Some(quantity_node(*quantity)),
+            NamespaceElement::Rational(rational) => Some(ASTNode::new(ASTNodeType::Rational(rational.clone()), vec![])),
+            NamespaceElement::BigNum(decimal) => Some(ASTNode::delimeter(Token::Decimal(decimal.clone()))),
+            NamespaceElement::Complex(complex) => Some(complex_node(*complex)),
+            NamespaceElement::Matrix(matrix) => Some(matrix_node(matrix.clone())),
+            NamespaceElement::List(values) => Some(list_node(values)),
+            NamespaceElement::Boolean(value) => Some(ASTNode::delimeter(Token::Boolean(*value))),
             NamespaceElement::Function(_) => None,
             // _ => None,
         }
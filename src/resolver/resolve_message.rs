@@ -1,3 +1,5 @@
+use crate::tokenizer::Span;
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum ResolveMessageType {
     Error,
@@ -8,13 +10,16 @@ pub enum ResolveMessageType {
 #[derive(Clone, Debug, PartialEq)]
 pub struct ResolveMessage {
     pub msg_type: ResolveMessageType,
-    pub content: String
+    pub content: String,
+    /// Part of the line the message refers to
+    pub span: Option<Span>,
 }
 
 impl ResolveMessage {
     pub fn error(content: &str) -> Self {
         Self {
             content: content.into(),
+            span: None,
             msg_type: ResolveMessageType::Error
         }
     }
@@ -22,6 +27,7 @@ impl ResolveMessage {
     pub fn info(content: &str) -> Self {
         Self {
             content: content.into(),
+            span: None,
             msg_type: ResolveMessageType::Info
         }
     }
@@ -29,7 +35,13 @@ impl ResolveMessage {
     pub fn output(content: &str) -> Self {
         Self {
             content: content.into(),
+            span: None,
             msg_type: ResolveMessageType::Output
         }
     }
-}
\ No newline at end of file
+
+    pub fn with_span(mut self, span: Option<Span>) -> Self {
+        self.span = span;
+        self
+    }
+}
@@ -0,0 +1,226 @@
+use crate::parser::node::{ASTNode, ASTNodeType};
+
+use super::{as_quantity, builtins, list_node_to_vec};
+
+/// Rewrites an expression into a simpler equivalent one, ex. 2 x + 3 x to 5 x and x * x to x^2.
+/// Constants are folded, sums are collected into like terms and products into powers of their factors.
+/// Numbers are calculated as floats and divisions assume their divisor is not zero, ex. x / x is 1.
+pub fn simplify(node: &ASTNode) -> ASTNode {
+    build(&terms(node)).with_span(node.span)
+}
+
+/// Product of a coefficient and powers of factors, ex. 3 x^2 y
+#[derive(Clone, Debug)]
+struct Term {
+    coefficient: f64,
+    /// Bases with their nonzero exponents, every base appears once
+    factors: Vec<(ASTNode, f64)>,
+}
+
+impl Term {
+    fn number(num: f64) -> Self {
+        Term { coefficient: num, factors: vec![] }
+    }
+
+    fn factor(base: ASTNode, exp: f64) -> Self {
+        Term { coefficient: 1., factors: vec![(base, exp)] }
+    }
+
+    fn mul(&self, other: &Term) -> Term {
+        let mut out = Term { coefficient: self.coefficient * other.coefficient, ..self.clone() };
+        for (base, exp) in &other.factors {
+            match out.factors.iter_mut().find(|x| x.0 == *base) {
+                Some(factor) => factor.1 += exp,
+                None => out.factors.push((base.clone(), *exp)),
+            }
+        }
+        out.factors.retain(|x| x.1 != 0.);
+        out
+    }
+
+    /// Raises the term to a power, `None` if it would change the value, ex. (x^2)^0.5 is |x|, not x
+    fn pow(&self, exp: f64) -> Option<Term> {
+        let coefficient = self.coefficient.powf(exp);
+        if !coefficient.is_finite() || (exp.fract() != 0. && !self.factors.is_empty()) {
+            return None;
+        }
+        Some(Term { coefficient, factors: self.factors.iter().map(|(base, x)| (base.clone(), x * exp)).collect() })
+    }
+
+    /// Terms are alike if they differ only in the coefficient, ex. 2 x y and y x
+    fn is_like(&self, other: &Term) -> bool {
+        self.factors.len() == other.factors.len() && self.factors.iter().all(|x| other.factors.contains(x))
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        self.factors.is_empty().then_some(self.coefficient)
+    }
+
+    fn to_node(&self) -> ASTNode {
+        let power = |(base, exp): &(ASTNode, f64)| match exp.abs() {
+            1. => base.clone(),
+            exp => ASTNode::new(ASTNodeType::Power, vec![base.clone(), ASTNode::number(exp)]),
+        };
+        let product = |factors: Vec<ASTNode>| factors.into_iter().reduce(|a, b| ASTNode::new(ASTNodeType::Product, vec![a, b]));
+        let numerator = product(self.factors.iter().filter(|x| x.1 > 0.).map(power).collect());
+        let denominator = product(self.factors.iter().filter(|x| x.1 < 0.).map(power).collect());
+
+        let numerator = match (numerator, self.coefficient) {
+            (Some(x), 1.) => x,
+            (Some(x), coefficient) => ASTNode::new(ASTNodeType::Product, vec![ASTNode::number(coefficient), x]),
+            (None, coefficient) => ASTNode::number(coefficient),
+        };
+        match denominator {
+            Some(x) => ASTNode::new(ASTNodeType::Quotient, vec![numerator, x]),
+            None => numerator,
+        }
+    }
+}
+
+/// Adds up terms, combining the like ones, terms which cancel out are removed
+fn collect(terms: impl IntoIterator<Item = Term>) -> Vec<Term> {
+    let mut out: Vec<Term> = vec![];
+    for term in terms {
+        match out.iter_mut().find(|x| x.is_like(&term)) {
+            Some(like) => like.coefficient += term.coefficient,
+            None => out.push(term),
+        }
+    }
+    out.retain(|x| x.coefficient != 0.);
+    out
+}
+
+/// Splits an expression into a sum of terms, an empty sum is zero
+fn terms(node: &ASTNode) -> Vec<Term> {
+    match (&node.node_type, node.children.as_slice()) {
+        (ASTNodeType::Sum, [a, b]) => collect(terms(a).into_iter().chain(terms(b))),
+        (ASTNodeType::Difference, [a, b]) => {
+            let negated = terms(b).into_iter().map(|x| Term { coefficient: -x.coefficient, ..x });
+            collect(terms(a).into_iter().chain(negated))
+        }
+        (ASTNodeType::Product, [a, b]) => multiply(terms(a), terms(b)),
+        (ASTNodeType::Quotient, [a, b]) => divide(terms(a), terms(b)),
+        (ASTNodeType::Power, [a, b]) => power(terms(a), terms(b)),
+        _ => {
+            let node = simplify_children(node);
+            match number(&node) {
+                Some(num) => collect([Term::number(num)]),
+                None => vec![Term::factor(node, 1.)],
+            }
+        }
+    }
+}
+
+/// Multiplies sums, a single term is distributed over the other sum, ex. 2 (x + 1) is 2 x + 2
+fn multiply(a: Vec<Term>, b: Vec<Term>) -> Vec<Term> {
+    match (a.as_slice(), b.as_slice()) {
+        ([], _) | (_, []) => vec![],
+        ([x], terms) | (terms, [x]) => collect(terms.iter().map(|y| x.mul(y))),
+        _ => vec![Term::factor(build(&a), 1.).mul(&Term::factor(build(&b), 1.))],
+    }
+}
+
+fn divide(a: Vec<Term>, b: Vec<Term>) -> Vec<Term> {
+    let reciprocal = match b.as_slice() {
+        [] => return vec![Term::factor(ASTNode::new(ASTNodeType::Quotient, vec![build(&a), build(&b)]), 1.)],
+        [x] if x.coefficient != 0. => Term { coefficient: 1. / x.coefficient, factors: x.factors.iter().map(|(base, exp)| (base.clone(), -exp)).collect() },
+        _ => Term::factor(build(&b), -1.),
+    };
+    match a.as_slice() {
+        [_, _, ..] if b.len() > 1 => vec![Term::factor(build(&a), 1.).mul(&reciprocal)],
+        _ => collect(a.iter().map(|x| x.mul(&reciprocal))),
+    }
+}
+
+fn power(base: Vec<Term>, exp: Vec<Term>) -> Vec<Term> {
+    let exp_value = match exp.as_slice() {
+        [] => Some(0.),
+        [x] => x.as_number(),
+        _ => None,
+    };
+    let result = match (base.as_slice(), exp_value) {
+        (_, Some(0.)) => Some(vec![Term::number(1.)]),
+        ([], Some(exp)) if exp > 0. => Some(vec![]),
+        ([x], Some(exp)) => x.pow(exp).map(|x| collect([x])),
+        ([_, _, ..], Some(exp)) => Some(vec![Term::factor(build(&base), exp)]),
+        ([x], None) if x.as_number() == Some(1.) => Some(vec![Term::number(1.)]),
+        _ => None,
+    };
+    result.unwrap_or_else(|| vec![Term::factor(ASTNode::new(ASTNodeType::Power, vec![build(&base), build(&exp)]), 1.)])
+}
+
+/// Builds a sum of terms, terms with negative coefficients after the first one are subtracted
+fn build(terms: &[Term]) -> ASTNode {
+    let mut terms = terms.iter();
+    let first = match terms.next() {
+        Some(term) => term.to_node(),
+        None => return ASTNode::number(0.),
+    };
+    terms.fold(first, |out, term| {
+        if term.coefficient < 0. {
+            let term = Term { coefficient: -term.coefficient, ..term.clone() };
+            ASTNode::new(ASTNodeType::Difference, vec![out, term.to_node()])
+        } else {
+            ASTNode::new(ASTNodeType::Sum, vec![out, term.to_node()])
+        }
+    })
+}
+
+/// Reads a dimensionless number, quantities with units are left as they are
+fn number(node: &ASTNode) -> Option<f64> {
+    match node.node_type {
+        ASTNodeType::Delimeter(_) | ASTNodeType::Rational(_) | ASTNodeType::Quantity(_) => as_quantity(node)
+            .filter(|x| x.dimension.is_dimensionless())
+            .map(|x| x.value),
+        _ => None,
+    }
+}
+
+/// Simplifies the insides of a node which is not an operation, ex. the arguments of a function.
+/// Builtin functions of numbers are evaluated, ex. sqrt(4) is 2.
+fn simplify_children(node: &ASTNode) -> ASTNode {
+    let node = ASTNode { children: node.children.iter().map(simplify).collect(), ..node.clone() };
+    if let (ASTNodeType::Function(name), Some(args)) = (&node.node_type, node.children.first()) {
+        let args: Option<Vec<_>> = list_node_to_vec(args).iter().map(|x| number(x).map(crate::units::Quantity::number)).collect();
+        let result = builtins::get_builtin(name).zip(args).and_then(|(builtin, args)| builtin.call(&args).ok());
+        if let Some(result) = result {
+            return ASTNode::number(result.value).with_span(node.span);
+        }
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::parsers::parse, tokenizer::tokenize};
+
+    use super::simplify;
+
+    fn simplified(expression: &str) -> String {
+        simplify(&parse(&tokenize(expression).unwrap()).unwrap()).to_string()
+    }
+
+    #[test]
+    fn simplify_identities_and_constants() {
+        assert_eq!(simplified("x + 0"), "x");
+        assert_eq!(simplified("1 * x * 1"), "x");
+        assert_eq!(simplified("0 * x + 2 * 3"), "6");
+        assert_eq!(simplified("x^1 + y^0"), "x + 1");
+        assert_eq!(simplified("x / 1 - x"), "0");
+        assert_eq!(simplified("sqrt(4) x"), "2 x");
+    }
+
+    #[test]
+    fn simplify_like_terms_and_powers() {
+        assert_eq!(simplified("2x + 3x"), "5 x");
+        assert_eq!(simplified("x * x"), "x^2");
+        assert_eq!(simplified("x^2 * x^3 / x"), "x^4");
+        assert_eq!(simplified("2x + 3 + x - 1"), "3 x + 2");
+        assert_eq!(simplified("x * y * x"), "x^2 * y");
+        assert_eq!(simplified("2 (x + 1)"), "2 x + 2");
+        assert_eq!(simplified("(x + 1) * (x + 1)"), "(x + 1)^2");
+        assert_eq!(simplified("3 / x + x / x"), "3 / x + 1");
+        assert_eq!(simplified("y - 3 y"), "-2 y");
+        assert_eq!(simplified("0 - x"), "-x");
+    }
+}
@@ -0,0 +1,990 @@
+use crate::{parser::node::{ASTNode, ASTNodeType}, tokenizer::{Comparison, Token}, units::{Dimension, Quantity}};
+
+use super::as_quantity;
+
+/// Polynomials of a higher degree are not expanded, ex. x^20000 = 2 is solved numerically instead
+const MAX_DEGREE: usize = 100;
+
+/// A polynomial in a single unknown, `(coefficients[0] + coefficients[1] * x + ...) / denominator`.
+/// Terms which do not appear are `None`, as their unit is not known.
+/// The denominator is kept separately, so that x / 3 = x - 4 gives exactly 6.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polynomial {
+    pub coefficients: Vec<Option<Quantity>>,
+    pub denominator: Quantity,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Solution {
+    /// Real roots in ascending order, empty if there are none
+    Roots(Vec<Quantity>),
+    NoSolution,
+    Infinite,
+}
+
+/// Values of the unknown for which an inequality holds
+#[derive(Clone, Debug, PartialEq)]
+pub enum Interval {
+    /// The unknown compared to a bound, ex. x < 3
+    Bound(Comparison, Quantity),
+    All,
+    Empty,
+}
+
+/// Solution of a system of equations, values are in the order of the unknowns
+#[derive(Clone, Debug, PartialEq)]
+pub enum SystemSolution {
+    Unique(Vec<Quantity>),
+    NoSolution,
+    Infinite,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SolveError {
+    /// The expression is not a polynomial in the unknown, ex. 1/x or 2^x
+    NotPolynomial,
+    /// The expression is not linear in the unknowns, ex. x * y
+    NotLinear,
+    Error(String),
+}
+
+impl From<String> for SolveError {
+    fn from(err: String) -> Self {
+        SolveError::Error(err)
+    }
+}
+
+impl Polynomial {
+    fn unknown() -> Self {
+        Polynomial { coefficients: vec![None, Some(Quantity::number(1.))], denominator: Quantity::number(1.) }
+    }
+
+    fn constant(value: Quantity) -> Self {
+        Polynomial { coefficients: vec![Some(value)], denominator: Quantity::number(1.) }
+    }
+
+    fn is_constant(&self) -> bool {
+        self.coefficients.iter().skip(1).all(Option::is_none)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.coefficients.iter().all(|x| x.is_none_or(|x| x.value == 0.))
+    }
+
+    fn checked_add(&self, other: &Polynomial) -> Result<Polynomial, String> {
+        if self.denominator == other.denominator {
+            return Ok(Polynomial {
+                coefficients: add_coefficients(&self.coefficients, &other.coefficients)?,
+                denominator: self.denominator,
+            });
+        }
+        // a / b + c / d = (a * d + c * b) / (b * d)
+        Ok(Polynomial {
+            coefficients: add_coefficients(
+                &scale(&self.coefficients, other.denominator),
+                &scale(&other.coefficients, self.denominator)
+            )?,
+            denominator: self.denominator * other.denominator,
+        })
+    }
+
+    fn checked_sub(&self, other: &Polynomial) -> Result<Polynomial, String> {
+        self.checked_add(&Polynomial {
+            coefficients: scale(&other.coefficients, Quantity::number(-1.)),
+            denominator: other.denominator,
+        })
+    }
+
+    fn checked_mul(&self, other: &Polynomial) -> Result<Polynomial, String> {
+        let mut coefficients = vec![None; self.coefficients.len() + other.coefficients.len() - 1];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in other.coefficients.iter().enumerate() {
+                if let (Some(a), Some(b)) = (a, b) {
+                    coefficients[i + j] = add_terms(coefficients[i + j], Some(*a * *b))?;
+                }
+            }
+        }
+        Ok(Polynomial { coefficients, denominator: self.denominator * other.denominator })
+    }
+
+    /// Divides by a polynomial which does not depend on the unknown
+    fn divide(&self, divisor: &Polynomial) -> Polynomial {
+        Polynomial {
+            coefficients: scale(&self.coefficients, divisor.denominator),
+            denominator: self.denominator * divisor.coefficients[0].unwrap_or_else(|| Quantity::number(0.)),
+        }
+    }
+
+    fn degree(&self) -> usize {
+        self.coefficients.len() - 1
+    }
+
+    fn checked_powi(&self, exp: u32) -> Result<Polynomial, String> {
+        let mut result = Polynomial::constant(Quantity::number(1.));
+        for _ in 0..exp {
+            result = result.checked_mul(self)?;
+        }
+        Ok(result)
+    }
+}
+
+fn scale(coefficients: &[Option<Quantity>], factor: Quantity) -> Vec<Option<Quantity>> {
+    coefficients.iter().map(|x| x.map(|x| x * factor)).collect()
+}
+
+fn add_terms(a: Option<Quantity>, b: Option<Quantity>) -> Result<Option<Quantity>, String> {
+    match (a, b) {
+        (Some(a), Some(b)) => a.checked_add(b).map(Some),
+        (a, None) => Ok(a),
+        (None, b) => Ok(b),
+    }
+}
+
+fn add_coefficients(a: &[Option<Quantity>], b: &[Option<Quantity>]) -> Result<Vec<Option<Quantity>>, String> {
+    (0..a.len().max(b.len()))
+        .map(|i| add_terms(a.get(i).copied().flatten(), b.get(i).copied().flatten()))
+        .collect()
+}
+
+/// Subtracts terms, returning `None` if they cancel out
+fn subtract_terms(a: Option<Quantity>, b: Option<Quantity>) -> Result<Option<Quantity>, String> {
+    let difference = add_terms(a, b.map(|x| x * Quantity::number(-1.)))?;
+    let magnitude = a.map_or(0., |x| x.value.abs()).max(b.map_or(0., |x| x.value.abs()));
+    match difference {
+        // Allow for rounding errors, ex. 0.1x + 0.2x - 0.3x
+        Some(x) if x.value.abs() <= magnitude * 1e-12 => Ok(None),
+        x => Ok(x),
+    }
+}
+
+/// Expands an expression into a polynomial in the unknown
+pub fn polynomial_form(node: &ASTNode, unknown: &str) -> Result<Polynomial, SolveError> {
+    if let Some(value) = as_quantity(node) {
+        return Ok(Polynomial::constant(value));
+    }
+
+    match &node.node_type {
+        ASTNodeType::Delimeter(Token::Name(name)) if name == unknown => Ok(Polynomial::unknown()),
+        ASTNodeType::Sum => Ok(polynomial_form(&node.children[0], unknown)?.checked_add(&polynomial_form(&node.children[1], unknown)?)?),
+        ASTNodeType::Difference => Ok(polynomial_form(&node.children[0], unknown)?.checked_sub(&polynomial_form(&node.children[1], unknown)?)?),
+        ASTNodeType::Product => {
+            let (a, b) = (polynomial_form(&node.children[0], unknown)?, polynomial_form(&node.children[1], unknown)?);
+            if a.degree() + b.degree() > MAX_DEGREE {
+                return Err(SolveError::NotPolynomial);
+            }
+            Ok(a.checked_mul(&b)?)
+        },
+        ASTNodeType::Quotient => {
+            let (a, b) = (polynomial_form(&node.children[0], unknown)?, polynomial_form(&node.children[1], unknown)?);
+            if b.is_zero() {
+                Err(SolveError::Error("Divide by zero".into()))
+            } else if b.is_constant() {
+                Ok(a.divide(&b))
+            } else {
+                // 1 / x
+                Err(SolveError::NotPolynomial)
+            }
+        },
+        ASTNodeType::Power => {
+            let base = polynomial_form(&node.children[0], unknown)?;
+            match as_quantity(&node.children[1]) {
+                Some(exp) if exp.dimension.is_dimensionless() && exp.value >= 0. && exp.value.fract() == 0. => {
+                    if exp.value > MAX_DEGREE as f64 || base.degree() * exp.value as usize > MAX_DEGREE {
+                        return Err(SolveError::NotPolynomial);
+                    }
+                    Ok(base.checked_powi(exp.value as u32)?)
+                },
+                // x^-1, x^0.5 or 2^x
+                _ => Err(SolveError::NotPolynomial),
+            }
+        },
+        ASTNodeType::Conversion => polynomial_form(&node.children[0], unknown),
+        _ => Err(SolveError::NotPolynomial),
+    }
+}
+
+/// Solves `left = right` for an unknown appearing polynomially on either side, returning all real roots
+pub fn solve_polynomial(left: &ASTNode, right: &ASTNode, unknown: &str) -> Result<Solution, SolveError> {
+    let (left, right) = (polynomial_form(left, unknown)?, polynomial_form(right, unknown)?);
+    let coefficients = moved_to_left(&left, &right)?;
+
+    let degree = coefficients.len() - 1;
+    if degree == 0 {
+        return Ok(match coefficients[0] {
+            None => Solution::Infinite,
+            Some(_) => Solution::NoSolution,
+        });
+    }
+
+    let dimension = unknown_dimension(&coefficients)?;
+    let values: Vec<f64> = coefficients.iter().map(|x| x.map_or(0., |x| x.value)).collect();
+
+    Ok(Solution::Roots(real_roots(&values).into_iter().map(|x| Quantity::new(x, dimension)).collect()))
+}
+
+/// The coefficients a and b of `left = right` collected into a x + b = 0, if it is linear in the unknown
+pub fn linear_form(left: &ASTNode, right: &ASTNode, unknown: &str) -> Result<(Quantity, Quantity), SolveError> {
+    let (left, right) = (polynomial_form(left, unknown)?, polynomial_form(right, unknown)?);
+    let coefficients = moved_to_left(&left, &right)?;
+    match coefficients.as_slice() {
+        [b, Some(a)] if a.value != 0. => {
+            // A missing constant term has the dimension of the term with the unknown
+            let zero = *a * Quantity::new(0., unknown_dimension(&coefficients)?);
+            Ok((*a, b.unwrap_or(zero)))
+        }
+        _ => Err(SolveError::NotLinear),
+    }
+}
+
+/// Multiplies `left = right` by both denominators and moves everything to the left, giving the coefficients of p(x) = 0
+fn moved_to_left(left: &Polynomial, right: &Polynomial) -> Result<Vec<Option<Quantity>>, String> {
+    let (left_terms, right_terms) = (scale(&left.coefficients, right.denominator), scale(&right.coefficients, left.denominator));
+    let mut coefficients = (0..left_terms.len().max(right_terms.len()))
+        .map(|i| subtract_terms(left_terms.get(i).copied().flatten(), right_terms.get(i).copied().flatten()))
+        .collect::<Result<Vec<_>, _>>()?;
+    while coefficients.len() > 1 && coefficients.last() == Some(&None) {
+        coefficients.pop();
+    }
+    Ok(coefficients)
+}
+
+/// Solves `left < right`, or another comparison, for an unknown appearing linearly on either side
+pub fn solve_linear_inequality(left: &ASTNode, right: &ASTNode, unknown: &str, comparison: Comparison) -> Result<Interval, SolveError> {
+    let (left, right) = (polynomial_form(left, unknown)?, polynomial_form(right, unknown)?);
+    let coefficients = moved_to_left(&left, &right)?;
+    if coefficients.len() > 2 {
+        return Err(SolveError::NotLinear);
+    }
+    // Multiplying by a negative denominator reverses the comparison
+    let sign = (left.denominator.value * right.denominator.value).signum();
+    let constant = coefficients[0].map_or(0., |x| x.value);
+
+    match coefficients.get(1).copied().flatten() {
+        Some(slope) => {
+            let dimension = unknown_dimension(&coefficients)?;
+            let bound = Quantity::new(-constant / slope.value, dimension);
+            // a x + b < 0 is x < -b / a, dividing by a negative a reverses the comparison
+            Ok(Interval::Bound(if slope.value * sign < 0. { comparison.flipped() } else { comparison }, bound))
+        }
+        None if comparison.holds(constant * sign) => Ok(Interval::All),
+        None => Ok(Interval::Empty),
+    }
+}
+
+/// Finds the unit of the unknown from the units of the coefficients, a_k * x^k must have the same unit for every k
+fn unknown_dimension(coefficients: &[Option<Quantity>]) -> Result<Dimension, String> {
+    let degree = coefficients.len() - 1;
+    let leading = coefficients[degree].unwrap();
+    let dimension = match coefficients.iter().enumerate().find_map(|(k, x)| x.map(|x| (k, x))) {
+        // a_n * x^n = a_k * x^k, so the unit of x^(n - k) is [a_k / a_n]
+        Some((k, lowest)) if k < degree => (lowest.dimension / leading.dimension)
+            .powf(1. / (degree - k) as f64)
+            .ok_or("Equation has incompatible units")?,
+        // a_n * x^n = 0 does not tell the unit of x
+        _ => Dimension::dimensionless(),
+    };
+    for (k, x) in coefficients.iter().enumerate() {
+        if let Some(x) = x {
+            if x.dimension * dimension.powi(k as i32) != leading.dimension * dimension.powi(degree as i32) {
+                return Err("Equation has incompatible units".into());
+            }
+        }
+    }
+    Ok(dimension)
+}
+
+/// Finds real roots of a polynomial with the given coefficients in ascending order of powers.
+/// Quadratics and cubics are solved in closed form, higher degrees numerically.
+pub fn real_roots(coefficients: &[f64]) -> Vec<f64> {
+    let mut coefficients = coefficients.to_vec();
+    while coefficients.len() > 1 && coefficients.last() == Some(&0.) {
+        coefficients.pop();
+    }
+
+    let roots = match coefficients.len() - 1 {
+        0 => vec![],
+        1 => vec![-coefficients[0] / coefficients[1]],
+        2 => quadratic_roots(coefficients[2], coefficients[1], coefficients[0]),
+        3 => cubic_roots(coefficients[3], coefficients[2], coefficients[1], coefficients[0]),
+        _ => numeric_roots(&coefficients),
+    };
+    polished(&coefficients, roots)
+}
+
+/// Refines, sorts and deduplicates roots, -0 is written as 0
+fn polished(coefficients: &[f64], roots: Vec<f64>) -> Vec<f64> {
+    let mut roots: Vec<f64> = roots.into_iter()
+        .map(|x| polish_root(coefficients, x))
+        .map(|x| if x == 0. { 0. } else { x })
+        .collect();
+    roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    roots.dedup_by(|a, b| (*a - *b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.));
+    roots
+}
+
+fn evaluate(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().rev().fold(0., |acc, c| acc * x + c)
+}
+
+fn derivative(coefficients: &[f64]) -> Vec<f64> {
+    coefficients.iter().enumerate().skip(1).map(|(k, c)| k as f64 * c).collect()
+}
+
+fn quadratic_roots(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let discriminant = b * b - 4. * a * c;
+    if discriminant.abs() <= 1e-12 * (b * b).max((4. * a * c).abs()) {
+        vec![-b / (2. * a)]
+    } else if discriminant < 0. {
+        vec![]
+    } else {
+        // Avoids cancellation when b is close to the root of the discriminant
+        let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+        if q == 0. {
+            vec![0.]
+        } else {
+            vec![q / a, c / q]
+        }
+    }
+}
+
+fn cubic_roots(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    let (b, c, d) = (b / a, c / a, d / a);
+    // Substituting x = t - b / 3 gives t^3 + p * t + q = 0
+    let p = c - b * b / 3.;
+    let q = 2. * b * b * b / 27. - b * c / 3. + d;
+    let shift = -b / 3.;
+
+    let discriminant = (q / 2.).powi(2) + (p / 3.).powi(3);
+    if discriminant.abs() <= 1e-12 * ((q / 2.).powi(2) + (p / 3.).powi(3).abs()) {
+        if p == 0. {
+            vec![shift]
+        } else {
+            vec![3. * q / p + shift, -3. * q / (2. * p) + shift]
+        }
+    } else if discriminant > 0. {
+        let root = discriminant.sqrt();
+        vec![(-q / 2. + root).cbrt() + (-q / 2. - root).cbrt() + shift]
+    } else {
+        // Three real roots, found with the trigonometric method
+        let r = 2. * (-p / 3.).sqrt();
+        let phi = (3. * q / (p * r)).clamp(-1., 1.).acos() / 3.;
+        (0..3).map(|k| r * (phi - 2. * std::f64::consts::PI * k as f64 / 3.).cos() + shift).collect()
+    }
+}
+
+/// Every real root is either a critical point or lies between two neighbouring critical points,
+/// where the polynomial changes sign. The critical points are the roots of the derivative, so the roots
+/// are found from the cubic derivative up, one derivative at a time.
+fn numeric_roots(coefficients: &[f64]) -> Vec<f64> {
+    let mut derivatives = vec![coefficients.to_vec()];
+    while derivatives.last().unwrap().len() > 4 {
+        let next = derivative(derivatives.last().unwrap());
+        derivatives.push(next);
+    }
+    let cubic = derivatives.pop().unwrap();
+    let mut roots = real_roots(&cubic);
+    while let Some(polynomial) = derivatives.pop() {
+        roots = roots_between(&polynomial, &roots);
+        if !derivatives.is_empty() {
+            roots = polished(&polynomial, roots);
+        }
+    }
+    roots
+}
+
+/// Finds the roots of a polynomial given the roots of its derivative
+fn roots_between(coefficients: &[f64], critical: &[f64]) -> Vec<f64> {
+    let degree = coefficients.len() - 1;
+    // Cauchy's bound on the magnitude of the roots
+    let bound = 1. + coefficients[..degree].iter().map(|c| (c / coefficients[degree]).abs()).fold(0., f64::max);
+
+    let mut points = vec![-bound];
+    points.extend(critical.iter().filter(|x| x.abs() < bound));
+    points.push(bound);
+
+    let mut roots = vec![];
+    for window in points.windows(2) {
+        let (a, b) = (window[0], window[1]);
+        if is_root(coefficients, a) {
+            roots.push(a);
+        } else if evaluate(coefficients, a).signum() != evaluate(coefficients, b).signum() && !is_root(coefficients, b) {
+            roots.push(bisect(coefficients, a, b));
+        }
+    }
+    roots
+}
+
+/// Checks whether the polynomial is zero at x, up to rounding errors
+fn is_root(coefficients: &[f64], x: f64) -> bool {
+    let magnitude: f64 = coefficients.iter().enumerate().map(|(k, c)| (c * x.powi(k as i32)).abs()).sum();
+    evaluate(coefficients, x).abs() <= 1e-12 * magnitude
+}
+
+fn bisect(coefficients: &[f64], mut a: f64, mut b: f64) -> f64 {
+    let sign_a = evaluate(coefficients, a).signum();
+    for _ in 0..200 {
+        let middle = (a + b) / 2.;
+        if middle == a || middle == b {
+            break;
+        }
+        if evaluate(coefficients, middle).signum() == sign_a {
+            a = middle;
+        } else {
+            b = middle;
+        }
+    }
+    (a + b) / 2.
+}
+
+/// Improves a root with Newton's method, preferring an exact integer root if there is one nearby
+fn polish_root(coefficients: &[f64], mut x: f64) -> f64 {
+    let derivative = derivative(coefficients);
+    for _ in 0..8 {
+        let (value, slope) = (evaluate(coefficients, x), evaluate(&derivative, x));
+        if value == 0. || slope == 0. {
+            break;
+        }
+        let next = x - value / slope;
+        // Only accept steps which do not make the value worse
+        if !next.is_finite() || evaluate(coefficients, next).abs() > value.abs() {
+            break;
+        }
+        x = next;
+    }
+    if (x - x.round()).abs() < 1e-9 && evaluate(coefficients, x.round()) == 0. {
+        x.round()
+    } else {
+        x
+    }
+}
+
+/// A linear expression in several unknowns, `coefficients[0] * x + coefficients[1] * y + ... + constant`
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearCombination {
+    pub coefficients: Vec<Option<Quantity>>,
+    pub constant: Option<Quantity>,
+}
+
+impl LinearCombination {
+    fn unknown(index: usize, count: usize) -> Self {
+        let mut coefficients = vec![None; count];
+        coefficients[index] = Some(Quantity::number(1.));
+        LinearCombination { coefficients, constant: None }
+    }
+
+    fn constant(value: Quantity, count: usize) -> Self {
+        LinearCombination { coefficients: vec![None; count], constant: Some(value) }
+    }
+
+    /// Returns the value of the expression if it does not depend on any unknown
+    fn as_constant(&self) -> Option<Quantity> {
+        if self.coefficients.iter().all(Option::is_none) {
+            Some(self.constant.unwrap_or_else(|| Quantity::number(0.)))
+        } else {
+            None
+        }
+    }
+
+    fn checked_add(&self, other: &LinearCombination) -> Result<LinearCombination, String> {
+        Ok(LinearCombination {
+            coefficients: add_coefficients(&self.coefficients, &other.coefficients)?,
+            constant: add_terms(self.constant, other.constant)?,
+        })
+    }
+
+    fn scale(&self, factor: Quantity) -> LinearCombination {
+        LinearCombination {
+            coefficients: scale(&self.coefficients, factor),
+            constant: self.constant.map(|x| x * factor),
+        }
+    }
+}
+
+/// Collects like terms of an expression into a linear combination of the unknowns
+pub fn linear_combination(node: &ASTNode, unknowns: &[String]) -> Result<LinearCombination, SolveError> {
+    if let Some(value) = as_quantity(node) {
+        return Ok(LinearCombination::constant(value, unknowns.len()));
+    }
+
+    let children = |node: &ASTNode| -> Result<(LinearCombination, LinearCombination), SolveError> {
+        Ok((linear_combination(&node.children[0], unknowns)?, linear_combination(&node.children[1], unknowns)?))
+    };
+    match &node.node_type {
+        ASTNodeType::Delimeter(Token::Name(name)) => match unknowns.iter().position(|x| x == name) {
+            Some(index) => Ok(LinearCombination::unknown(index, unknowns.len())),
+            None => Err(SolveError::NotLinear),
+        },
+        ASTNodeType::Sum => {
+            let (a, b) = children(node)?;
+            Ok(a.checked_add(&b)?)
+        },
+        ASTNodeType::Difference => {
+            let (a, b) = children(node)?;
+            Ok(a.checked_add(&b.scale(Quantity::number(-1.)))?)
+        },
+        ASTNodeType::Product => match children(node)? {
+            (a, b) if a.as_constant().is_some() => Ok(b.scale(a.as_constant().unwrap())),
+            (a, b) if b.as_constant().is_some() => Ok(a.scale(b.as_constant().unwrap())),
+            // x * y
+            _ => Err(SolveError::NotLinear),
+        },
+        ASTNodeType::Quotient => match children(node)? {
+            (_, b) if b.as_constant().is_some_and(|x| x.value == 0.) => Err(SolveError::Error("Divide by zero".into())),
+            (a, b) if b.as_constant().is_some() => Ok(a.scale(Quantity::number(1.) / b.as_constant().unwrap())),
+            _ => Err(SolveError::NotLinear),
+        },
+        ASTNodeType::Power => match as_quantity(&node.children[1]) {
+            Some(exp) if exp == Quantity::number(1.) => linear_combination(&node.children[0], unknowns),
+            _ => Err(SolveError::NotLinear),
+        },
+        ASTNodeType::Conversion => linear_combination(&node.children[0], unknowns),
+        _ => Err(SolveError::NotLinear),
+    }
+}
+
+/// Solves a system of linear equations `left = right` with as many equations as unknowns
+pub fn solve_linear_system(equations: &[(&ASTNode, &ASTNode)], unknowns: &[String]) -> Result<SystemSolution, SolveError> {
+    // Every equation is brought to the form a_1 * x_1 + ... + a_n * x_n = b
+    let rows = equations.iter()
+        .map(|(left, right)| {
+            let (left, right) = (linear_combination(left, unknowns)?, linear_combination(right, unknowns)?);
+            Ok(left.checked_add(&right.scale(Quantity::number(-1.)))?.scale(Quantity::number(-1.)))
+        })
+        .collect::<Result<Vec<LinearCombination>, SolveError>>()?;
+    let dimensions = unknown_dimensions(&rows, unknowns.len())?;
+
+    let mut matrix: Vec<Vec<f64>> = rows.iter()
+        .map(|row| {
+            let mut values: Vec<f64> = row.coefficients.iter().map(|x| x.map_or(0., |x| -x.value)).collect();
+            values.push(row.constant.map_or(0., |x| x.value));
+            values
+        })
+        .collect();
+
+    match gaussian_elimination(&mut matrix, unknowns.len()) {
+        Some(values) => Ok(SystemSolution::Unique(values.into_iter().zip(dimensions).map(|(x, d)| Quantity::new(x, d)).collect())),
+        // The system is singular, it has no solution if an equation reduced to 0 = b with a non-zero b
+        None if matrix.iter().any(|row| row[..unknowns.len()].iter().all(|x| *x == 0.) && row[unknowns.len()] != 0.) => Ok(SystemSolution::NoSolution),
+        None => Ok(SystemSolution::Infinite),
+    }
+}
+
+/// Finds the unit of every unknown, as every term of an equation must have the same unit
+fn unknown_dimensions(rows: &[LinearCombination], count: usize) -> Result<Vec<Dimension>, String> {
+    let mut unknowns: Vec<Option<Dimension>> = vec![None; count];
+    let mut equations: Vec<Option<Dimension>> = rows.iter().map(|row| row.constant.map(|x| x.dimension)).collect();
+
+    // Propagate known units between equations and unknowns until nothing changes
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (row, equation) in rows.iter().zip(equations.iter_mut()) {
+            for (coefficient, unknown) in row.coefficients.iter().zip(unknowns.iter_mut()) {
+                let coefficient = match coefficient {
+                    Some(x) => x.dimension,
+                    None => continue,
+                };
+                match (*equation, *unknown) {
+                    (Some(equation), None) => { *unknown = Some(equation / coefficient); changed = true; },
+                    (None, Some(unknown)) => { *equation = Some(coefficient * unknown); changed = true; },
+                    (Some(equation), Some(unknown)) if coefficient * unknown != equation => return Err("Equations have incompatible units".into()),
+                    _ => (),
+                }
+            }
+        }
+    }
+    Ok(unknowns.into_iter().map(|x| x.unwrap_or_default()).collect())
+}
+
+/// Solves an augmented matrix with partial pivoting, returning `None` if the system is singular.
+/// The matrix is left in row echelon form.
+fn gaussian_elimination(matrix: &mut [Vec<f64>], count: usize) -> Option<Vec<f64>> {
+    let scale = matrix.iter().flatten().fold(0., |acc: f64, x| acc.max(x.abs()));
+    let mut singular = false;
+    let mut pivot_row = 0;
+    let mut pivots = vec![];
+    for column in 0..count {
+        let best = (pivot_row..matrix.len()).max_by(|a, b| matrix[*a][column].abs().partial_cmp(&matrix[*b][column].abs()).unwrap());
+        let best = match best {
+            Some(best) if matrix[best][column].abs() > 1e-12 * scale => best,
+            _ => { singular = true; continue; },
+        };
+        matrix.swap(pivot_row, best);
+        for row in (pivot_row + 1)..matrix.len() {
+            let factor = matrix[row][column] / matrix[pivot_row][column];
+            let pivot = matrix[pivot_row].clone();
+            for (x, p) in matrix[row].iter_mut().zip(pivot).skip(column) {
+                *x -= factor * p;
+            }
+            // Treat rounding errors as exact cancellation
+            for x in matrix[row].iter_mut() {
+                if x.abs() <= 1e-12 * scale {
+                    *x = 0.;
+                }
+            }
+        }
+        pivots.push(column);
+        pivot_row += 1;
+    }
+    if singular || pivot_row < count {
+        return None;
+    }
+
+    let mut values = vec![0.; count];
+    for (row, column) in pivots.into_iter().enumerate().rev() {
+        let sum: f64 = ((column + 1)..count).map(|i| matrix[row][i] * values[i]).sum();
+        values[column] = (matrix[row][count] - sum) / matrix[row][column];
+    }
+    Some(values)
+}
+
+/// A root found numerically, the exact root lies within `tolerance` of `value`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NumericRoot {
+    pub value: f64,
+    pub tolerance: f64,
+}
+
+/// Finds a root of `f` numerically, preferring the one closest to zero.
+/// `f` returns `None` where it is undefined, ex. ln(x) for negative x.
+pub fn find_root<F: FnMut(f64) -> Option<f64>>(mut f: F) -> Result<NumericRoot, String> {
+    // Look for sign changes on a grid spanning several orders of magnitude
+    let mut points = vec![0.];
+    for exp in -3..=6 {
+        for mantissa in [1., 2., 5.] {
+            let x = mantissa * 10f64.powi(exp);
+            points.extend([x, -x]);
+        }
+    }
+    points.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let samples: Vec<(f64, Option<f64>)> = points.iter().map(|x| (*x, f(*x).filter(|y| y.is_finite()))).collect();
+
+    if let Some((x, _)) = samples.iter().filter(|(_, y)| *y == Some(0.)).min_by(|a, b| a.0.abs().partial_cmp(&b.0.abs()).unwrap()) {
+        return Ok(NumericRoot { value: *x, tolerance: 0. });
+    }
+
+    let mut brackets: Vec<(f64, f64, f64, f64)> = samples.windows(2)
+        .filter_map(|window| match window {
+            [(a, Some(fa)), (b, Some(fb))] if fa.signum() != fb.signum() => Some((*a, *b, *fa, *fb)),
+            _ => None,
+        })
+        .collect();
+    brackets.sort_by(|a, b| a.0.abs().min(a.1.abs()).partial_cmp(&b.0.abs().min(b.1.abs())).unwrap());
+
+    for (a, b, fa, fb) in brackets {
+        if let Some(root) = refine_bracket(&mut f, a, b, fa) {
+            // A sign change can also be a pole, ex. tan(x) around pi / 2
+            if f(root.value).is_some_and(|y| y.abs() <= 1e-6 * fa.abs().max(fb.abs()).max(1.)) {
+                return Ok(root);
+            }
+        }
+    }
+
+    // Without a sign change, the root may only touch zero, ex. cos(x) = 1
+    let start = samples.iter()
+        .filter_map(|(x, y)| y.map(|y| (*x, y.abs())))
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    match start.and_then(|(x, _)| newton(&mut f, x)) {
+        Some(root) => Ok(root),
+        None => Err("Could not find a solution, the numeric method did not converge".into()),
+    }
+}
+
+/// Newton's method safeguarded by bisection, the root stays between a and b
+fn refine_bracket<F: FnMut(f64) -> Option<f64>>(f: &mut F, mut a: f64, mut b: f64, fa: f64) -> Option<NumericRoot> {
+    let mut x = (a + b) / 2.;
+    for _ in 0..200 {
+        let fx = f(x)?;
+        if fx == 0. {
+            return Some(NumericRoot { value: x, tolerance: 0. });
+        }
+        if fx.signum() == fa.signum() {
+            a = x;
+        } else {
+            b = x;
+        }
+
+        let h = 1e-7 * x.abs().max(1.);
+        let next = match f(x + h) {
+            Some(fh) if fh != fx => x - fx * h / (fh - fx),
+            _ => (a + b) / 2.,
+        };
+        let next = if next > a.min(b) && next < a.max(b) { next } else { (a + b) / 2. };
+
+        let step = (next - x).abs();
+        if step <= 4. * f64::EPSILON * x.abs().max(1.) || (b - a).abs() <= 4. * f64::EPSILON * x.abs().max(1.) {
+            return Some(NumericRoot { value: next, tolerance: step.min((b - a).abs() / 2.) });
+        }
+        x = next;
+    }
+    Some(NumericRoot { value: x, tolerance: (b - a).abs() / 2. })
+}
+
+fn newton<F: FnMut(f64) -> Option<f64>>(f: &mut F, mut x: f64) -> Option<NumericRoot> {
+    for _ in 0..200 {
+        let fx = f(x)?;
+        if fx == 0. {
+            return Some(NumericRoot { value: x, tolerance: 0. });
+        }
+        let h = 1e-7 * x.abs().max(1.);
+        let slope = (f(x + h)? - fx) / h;
+        if slope == 0. || !slope.is_finite() {
+            return None;
+        }
+        let step = fx / slope;
+        x -= step;
+        if step.abs() <= 1e-12 * x.abs().max(1.) {
+            return Some(NumericRoot { value: x, tolerance: step.abs() });
+        }
+    }
+    None
+}
+
+pub const UNDEFINED_INTEGRAL: &str = "The integral is undefined, the integrand is not finite on the interval";
+
+/// Evaluations of the integrand allowed, integrals which need more are reported as not converging
+const MAX_INTEGRAND_EVALUATIONS: usize = 30_000;
+
+const KRONROD_POINTS: usize = 15;
+
+/// Nodes of the 15 point Kronrod rule on [-1, 1], the odd ones are the nodes of the 7 point Gauss rule
+const KRONROD_NODES: [f64; 8] = [
+    0.9914553711208126, 0.9491079123427585, 0.8648644233597691, 0.7415311855993945,
+    0.5860872354676911, 0.4058451513773972, 0.20778495500789848, 0.,
+];
+const KRONROD_WEIGHTS: [f64; 8] = [
+    0.022935322010529224, 0.06309209262997856, 0.10479001032225019, 0.14065325971552592,
+    0.1690047266392679, 0.19035057806478542, 0.20443294007529889, 0.20948214108472782,
+];
+const GAUSS_WEIGHTS: [f64; 4] = [
+    0.1294849661688697, 0.27970539148927664, 0.3818300505051189, 0.4179591836734694,
+];
+
+/// Definite integral of `f` from a to b by adaptive Gauss-Kronrod quadrature, the interval with the largest error is halved
+/// until the errors are small enough. The endpoints are never evaluated, so integrable singularities there are allowed,
+/// ex. 1 / sqrt(x) from 0 to 1. `f` returns `None` where it is undefined, which makes the integral undefined.
+pub fn integrate<F: FnMut(f64) -> Option<f64>>(mut f: F, a: f64, b: f64) -> Result<f64, String> {
+    if a == b {
+        return Ok(0.);
+    }
+    if b < a {
+        return integrate(f, b, a).map(|x| -x);
+    }
+    let mut f = |x: f64| f(x).filter(|y| y.is_finite()).ok_or_else(|| UNDEFINED_INTEGRAL.to_string());
+    let mut evaluations = KRONROD_POINTS;
+    // Intervals with their estimates of the integral and of its error
+    let mut intervals = vec![(a, b, gauss_kronrod(&mut f, a, b)?)];
+    loop {
+        let value: f64 = intervals.iter().map(|x| x.2.0).sum();
+        let error: f64 = intervals.iter().map(|x| x.2.1).sum();
+        let tolerance = 1e-12 * value.abs().max(1e-3);
+        if error <= tolerance {
+            // Digits below the tolerance are noise
+            let scale = 10f64.powi(-tolerance.log10().floor() as i32);
+            return Ok((value * scale).round() / scale + 0.);
+        }
+        let worst = (0..intervals.len()).max_by(|i, j| intervals[*i].2.1.total_cmp(&intervals[*j].2.1)).unwrap();
+        let (start, end, _) = intervals.swap_remove(worst);
+        let middle = (start + end) / 2.;
+        if middle <= start || middle >= end || evaluations >= MAX_INTEGRAND_EVALUATIONS {
+            return Err("The integral did not converge".into());
+        }
+        intervals.push((start, middle, gauss_kronrod(&mut f, start, middle)?));
+        intervals.push((middle, end, gauss_kronrod(&mut f, middle, end)?));
+        evaluations += 2 * KRONROD_POINTS;
+    }
+}
+
+/// Integral of `f` over [a, b] by the 15 point Kronrod rule, with the difference from the 7 point Gauss rule as its error
+fn gauss_kronrod<F: FnMut(f64) -> Result<f64, String>>(f: &mut F, a: f64, b: f64) -> Result<(f64, f64), String> {
+    let (center, half) = ((a + b) / 2., (b - a) / 2.);
+    let at_center = f(center)?;
+    let mut kronrod = at_center * KRONROD_WEIGHTS[7];
+    let mut gauss = at_center * GAUSS_WEIGHTS[3];
+    for j in 0..7 {
+        let offset = half * KRONROD_NODES[j];
+        let sum = f(center - offset)? + f(center + offset)?;
+        kronrod += KRONROD_WEIGHTS[j] * sum;
+        if j % 2 == 1 {
+            gauss += GAUSS_WEIGHTS[j / 2] * sum;
+        }
+    }
+    Ok((kronrod * half, ((kronrod - gauss) * half).abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::parsers::parse, tokenizer::{tokenize, Comparison}, units::{Dimension, Quantity}};
+
+    use super::{find_root, integrate, real_roots, solve_linear_inequality, solve_linear_system, solve_polynomial, Interval, Solution, SolveError, SystemSolution, UNDEFINED_INTEGRAL};
+
+    fn solve(equation: &str) -> Result<Solution, SolveError> {
+        let tree = parse(&tokenize(equation).unwrap()).unwrap();
+        solve_polynomial(&tree.children[0], &tree.children[1], "x")
+    }
+
+    fn roots(values: &[f64]) -> Result<Solution, SolveError> {
+        Ok(Solution::Roots(values.iter().map(|x| Quantity::number(*x)).collect()))
+    }
+
+    #[test]
+    fn solve_polynomial_collects_like_terms() {
+        assert_eq!(solve("2x + x = 9"), roots(&[3.]));
+        assert_eq!(solve("x / 4 = x - 6"), roots(&[8.]));
+        assert_eq!(solve("2(x + 1) = x"), roots(&[-2.]));
+        assert_eq!(solve("x / 3 = x - 4"), roots(&[6.]));
+    }
+
+    #[test]
+    fn solve_polynomial_degenerate() {
+        assert_eq!(solve("x + 1 = x + 2"), Ok(Solution::NoSolution));
+        assert_eq!(solve("2x - x = x"), Ok(Solution::Infinite));
+        assert_eq!(solve("0.1x + 0.2x = 0.3x"), Ok(Solution::Infinite));
+        assert_eq!(solve("x^2 = -1"), roots(&[]));
+    }
+
+    #[test]
+    fn solve_polynomial_finds_all_roots() {
+        assert_eq!(solve("x^2 - x = 6"), roots(&[-2., 3.]));
+        assert_eq!(solve("(x - 1)^2 = 0"), roots(&[1.]));
+        assert_eq!(solve("x^3 - 6x^2 + 11x = 6"), roots(&[1., 2., 3.]));
+        assert_eq!(solve("x^3 = 8"), roots(&[2.]));
+        assert_eq!(solve("x^4 - 5x^2 + 4 = 0"), roots(&[-2., -1., 1., 2.]));
+    }
+
+    #[test]
+    fn solve_polynomial_keeps_units() {
+        let tree = parse(&tokenize("x^2 = 9 [m^2]").unwrap()).unwrap();
+        let meters = Dimension([1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            solve_polynomial(&tree.children[0], &tree.children[1], "x"),
+            Ok(Solution::Roots(vec![Quantity::new(-3., meters), Quantity::new(3., meters)]))
+        );
+    }
+
+    #[test]
+    fn solve_polynomial_rejects_other_equations() {
+        assert_eq!(solve("1 / x + x = 4"), Err(SolveError::NotPolynomial));
+        assert_eq!(solve("2^x = 4"), Err(SolveError::NotPolynomial));
+    }
+
+    #[test]
+    fn solve_linear_inequality_into_interval() {
+        let solve = |inequality: &str| {
+            let tree = parse(&tokenize(inequality).unwrap()).unwrap();
+            let comparison = match tree.node_type {
+                crate::parser::node::ASTNodeType::Comparison(comparison) => comparison,
+                _ => panic!("{} is not a comparison", inequality),
+            };
+            solve_linear_inequality(&tree.children[0], &tree.children[1], "x", comparison)
+        };
+        assert_eq!(solve("2x + 1 < 7"), Ok(Interval::Bound(Comparison::Less, Quantity::number(3.))));
+        assert_eq!(solve("5 - x >= 1"), Ok(Interval::Bound(Comparison::LessEqual, Quantity::number(4.))));
+        assert_eq!(solve("x / -2 > 1"), Ok(Interval::Bound(Comparison::Less, Quantity::number(-2.))));
+        assert_eq!(solve("x + 1 > x"), Ok(Interval::All));
+        assert_eq!(solve("x <= x - 1"), Ok(Interval::Empty));
+        assert_eq!(solve("x^2 < 4"), Err(SolveError::NotLinear));
+    }
+
+    #[test]
+    fn real_roots_of_high_degree() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)(x - 5)
+        let roots = real_roots(&[-120., 274., -225., 85., -15., 1.]);
+        assert_eq!(roots, vec![1., 2., 3., 4., 5.]);
+        // (x^2 + 1)(x - 2)^2 touches zero at 2
+        assert_eq!(real_roots(&[4., -4., 5., -4., 1.]), vec![2.]);
+        // x^60 - 1, the derivatives are not solved recursively
+        let mut coefficients = vec![0.; 61];
+        coefficients[0] = -1.;
+        coefficients[60] = 1.;
+        assert_eq!(real_roots(&coefficients), vec![-1., 1.]);
+        // Roots are never negative zero
+        assert!(real_roots(&[0., 0., 1.])[0].is_sign_positive());
+        assert!(real_roots(&[0., 0., 0., 1.])[0].is_sign_positive());
+    }
+
+    #[test]
+    fn solve_polynomial_caps_the_degree() {
+        assert_eq!(solve("x^20000 = 2"), Err(SolveError::NotPolynomial));
+        assert_eq!(solve("x^60 * x^60 = 2"), Err(SolveError::NotPolynomial));
+    }
+
+    #[test]
+    fn find_root_brackets_and_refines() {
+        let root = find_root(|x| Some(x.sin() - 0.5)).unwrap();
+        assert!((root.value - std::f64::consts::FRAC_PI_6).abs() <= 1e-15);
+        assert!(root.tolerance <= 1e-12);
+
+        let root = find_root(|x| Some(x.exp() - 10.)).unwrap();
+        assert!((root.value - 10f64.ln()).abs() <= 1e-14);
+
+        // Undefined for x <= 0
+        let root = find_root(|x| if x > 0. { Some(x.ln() - 1.) } else { None }).unwrap();
+        assert!((root.value - std::f64::consts::E).abs() <= 1e-14);
+    }
+
+    #[test]
+    fn find_root_skips_poles() {
+        // tan changes sign at pi / 2 without crossing zero, the closest root is 0
+        let root = find_root(|x| Some(x.tan() - 1e-3)).unwrap();
+        assert!(root.value.abs() <= 1e-2);
+    }
+
+    #[test]
+    fn find_root_reports_failure() {
+        assert!(find_root(|x| Some(x.exp() + 1.)).is_err());
+    }
+
+    #[test]
+    fn integrate_adaptively() {
+        let close = |a: f64, b: f64| (a - b).abs() <= 1e-10;
+        assert!(close(integrate(|x| Some(x * x), 0., 3.).unwrap(), 9.));
+        assert!(close(integrate(|x| Some(x.sin()), 0., std::f64::consts::PI).unwrap(), 2.));
+        assert!(close(integrate(|x| Some(x.sqrt()), 0., 1.).unwrap(), 2. / 3.));
+        assert!(close(integrate(|x| Some(x.exp()), 1., 0.).unwrap(), 1. - std::f64::consts::E));
+        // Singularities at the endpoints are not evaluated
+        assert!(close(integrate(|x| Some(1. / x.sqrt()), 0., 1.).unwrap(), 2.));
+        assert!(close(integrate(|x| Some(x.ln()), 0., 1.).unwrap(), -1.));
+        assert!(integrate(|x| Some(1. / x), 0., 1.).is_err());
+        assert_eq!(integrate(|x| if x < 0.5 { Some(1.) } else { None }, 0., 1.), Err(UNDEFINED_INTEGRAL.into()));
+        // Too many oscillations to converge within the budget
+        assert_eq!(integrate(|x| Some((1000. * x).sin()), 0., 100.), Err("The integral did not converge".into()));
+        assert_eq!(integrate(|x| Some((1. / x).sin()), 1e-5, 1.), Err("The integral did not converge".into()));
+    }
+
+    fn solve_system(equations: &[&str], unknowns: &[&str]) -> Result<SystemSolution, SolveError> {
+        let trees: Vec<_> = equations.iter().map(|x| parse(&tokenize(x).unwrap()).unwrap()).collect();
+        let equations: Vec<_> = trees.iter().map(|x| (&x.children[0], &x.children[1])).collect();
+        let unknowns: Vec<String> = unknowns.iter().map(|x| x.to_string()).collect();
+        solve_linear_system(&equations, &unknowns)
+    }
+
+    #[test]
+    fn solve_linear_system_unique() {
+        assert_eq!(
+            solve_system(&["x + y = 10", "x - y = 2"], &["x", "y"]),
+            Ok(SystemSolution::Unique(vec![Quantity::number(6.), Quantity::number(4.)]))
+        );
+        assert_eq!(
+            solve_system(&["x + y = 3", "y + z = 5", "x + z = 4"], &["x", "y", "z"]),
+            Ok(SystemSolution::Unique(vec![Quantity::number(1.), Quantity::number(2.), Quantity::number(3.)]))
+        );
+    }
+
+    #[test]
+    fn solve_linear_system_singular() {
+        assert_eq!(solve_system(&["x + y = 1", "2x + 2y = 3"], &["x", "y"]), Ok(SystemSolution::NoSolution));
+        assert_eq!(solve_system(&["x + y = 1", "2x + 2y = 2"], &["x", "y"]), Ok(SystemSolution::Infinite));
+        assert_eq!(solve_system(&["x * y = 1", "x = 2"], &["x", "y"]), Err(SolveError::NotLinear));
+    }
+
+    #[test]
+    fn solve_linear_system_keeps_units() {
+        let meters = Dimension([1, 0, 0, 0, 0, 0, 0]);
+        let seconds = Dimension([0, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(
+            solve_system(&["d = 2 [m/s] * t", "d + 1 [m] = 5 [m]"], &["d", "t"]),
+            Ok(SystemSolution::Unique(vec![Quantity::new(4., meters), Quantity::new(2., seconds)]))
+        );
+    }
+}
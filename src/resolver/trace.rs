@@ -0,0 +1,90 @@
+use crate::{parser::node::{ASTNode, ASTNodeType}, tokenizer::Token, units::Quantity};
+
+use super::quantity_node;
+
+/// Steps which isolate the unknown of an equation, each applying one operation to both sides
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolutionTrace {
+    /// The equation with known values substituted
+    pub equation: ASTNode,
+    pub steps: Vec<TraceStep>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceStep {
+    /// The inverse operation, ex. "subtract 2 from both sides"
+    pub operation: String,
+    /// The equation after the operation
+    pub equation: ASTNode,
+}
+
+impl SolutionTrace {
+    pub fn new(equation: ASTNode) -> Self {
+        SolutionTrace { equation, steps: vec![] }
+    }
+
+    /// Records a step, the side with the unknown stays on the side it was written on
+    fn push(&mut self, operation: String, unknown_side: ASTNode, value: Quantity, unknown_index: usize) {
+        let mut sides = vec![unknown_side, quantity_node(value)];
+        if unknown_index == 1 {
+            sides.reverse();
+        }
+        self.steps.push(TraceStep { operation, equation: ASTNode::new(ASTNodeType::Equality, sides) });
+    }
+
+    /// Records how an operation with the known value `a` is undone, leaving `unknown_side` equal to `value`.
+    /// `unknown_on_left` tells if the unknown is the left operand, ex. x - 2 rather than 2 - x.
+    pub fn undo(&mut self, operation: &ASTNodeType, a: Quantity, unknown_on_left: bool, unknown_side: &ASTNode, value: Quantity, unknown_index: usize) {
+        let step = match (operation, unknown_on_left) {
+            (ASTNodeType::Sum, _) => format!("subtract {} from both sides", a),
+            (ASTNodeType::Difference, true) => format!("add {} to both sides", a),
+            // 2 - x = 10 is -x = 8, then x = -8
+            (ASTNodeType::Difference, false) => {
+                let negated = ASTNode::new(ASTNodeType::Product, vec![ASTNode::number(-1.), unknown_side.clone()]);
+                self.push(format!("subtract {} from both sides", a), negated, value * Quantity::number(-1.), unknown_index);
+                "multiply both sides by -1".into()
+            }
+            (ASTNodeType::Product, _) => format!("divide both sides by {}", a),
+            (ASTNodeType::Quotient, true) => format!("multiply both sides by {}", a),
+            // 2 / x = 4 is x / 2 = 1/4, then x = 1/2
+            (ASTNodeType::Quotient, false) => {
+                let reciprocal = ASTNode::new(ASTNodeType::Quotient, vec![unknown_side.clone(), quantity_node(a)]);
+                self.push("take the reciprocal of both sides".into(), reciprocal, value / a, unknown_index);
+                format!("multiply both sides by {}", a)
+            }
+            _ => return,
+        };
+        self.push(step, unknown_side.clone(), value, unknown_index);
+    }
+
+    /// Records how a linear equation, written as a x + b = 0 after collecting its terms, is solved, returning the solution
+    pub fn collect_linear(&mut self, unknown: &str, a: Quantity, b: Quantity) -> Quantity {
+        let unknown = ASTNode::delimeter(Token::Name(unknown.to_string()));
+        let term = if a == Quantity::number(1.) {
+            unknown.clone()
+        } else {
+            ASTNode::new(ASTNodeType::Product, vec![quantity_node(a), unknown.clone()])
+        };
+        let zero = Quantity { value: 0., ..b };
+        // 0 - b rather than -b, so that -0 is not printed
+        let negated = Quantity { value: 0. - b.value, ..b };
+        let solution = negated / a;
+        let solution = Quantity { value: solution.value + 0., ..solution };
+        if b.value == 0. {
+            self.push("collect the terms on the left side".into(), term.clone(), zero, 0);
+        } else {
+            let (sides, step) = if b.value < 0. {
+                (ASTNodeType::Difference, format!("add {} to both sides", negated))
+            } else {
+                (ASTNodeType::Sum, format!("subtract {} from both sides", b))
+            };
+            let collected = ASTNode::new(sides, vec![term.clone(), quantity_node(if b.value < 0. { negated } else { b })]);
+            self.push("collect the terms on the left side".into(), collected, zero, 0);
+            self.push(step, term.clone(), negated, 0);
+        }
+        if term != unknown {
+            self.push(format!("divide both sides by {}", a), unknown, solution, 0);
+        }
+        solution
+    }
+}
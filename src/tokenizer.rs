@@ -1,16 +1,24 @@
+use crate::number::decimal::Decimal;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     Name(String),
     Number(f64),
+    /// Number literal kept with all of its digits, see `NumberLiterals::Decimal`
+    Decimal(Decimal),
     Boolean(bool),
     Operation(Operation),
     Equals,
+    Comparison(Comparison),
+    Logic(Logic),
     OpeningParen,
     ClosingParen,
     OpeningBracket,
     ClosingBracket,
     Comma,
     Let,
+    /// `to` or `in`, ex. 3 [km] to [mi]
+    Conversion,
     Empty
 }
 
@@ -19,142 +27,239 @@ pub enum Operation {
     Add, Sub, Mul, Div, Exp
 }
 
+/// `<`, `>`, `<=`, `>=` or `!=`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less, Greater, LessEqual, GreaterEqual, NotEqual
+}
+
+impl Comparison {
+    /// Compares a value to zero, ex. the difference of both sides
+    pub fn holds(self, value: f64) -> bool {
+        match self {
+            Comparison::Less => value < 0.,
+            Comparison::Greater => value > 0.,
+            Comparison::LessEqual => value <= 0.,
+            Comparison::GreaterEqual => value >= 0.,
+            Comparison::NotEqual => value != 0.,
+        }
+    }
+
+    /// The comparison with its sides swapped, ex. 3 > x is x < 3
+    pub fn flipped(self) -> Self {
+        match self {
+            Comparison::Less => Comparison::Greater,
+            Comparison::Greater => Comparison::Less,
+            Comparison::LessEqual => Comparison::GreaterEqual,
+            Comparison::GreaterEqual => Comparison::LessEqual,
+            Comparison::NotEqual => Comparison::NotEqual,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::Greater => ">",
+            Comparison::LessEqual => "<=",
+            Comparison::GreaterEqual => ">=",
+            Comparison::NotEqual => "!=",
+        }
+    }
+}
+
+/// `and`, `or`, `xor` or `not`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Logic {
+    And, Or, Xor, Not
+}
+
+impl Logic {
+    pub fn name(self) -> &'static str {
+        match self {
+            Logic::And => "and",
+            Logic::Or => "or",
+            Logic::Xor => "xor",
+            Logic::Not => "not",
+        }
+    }
+
+    /// Applies the operation to its operands, `not` only uses the first one
+    pub fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            Logic::And => a && b,
+            Logic::Or => a || b,
+            Logic::Xor => a != b,
+            Logic::Not => !a,
+        }
+    }
+}
+
+/// How number literals are tokenized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberLiterals {
+    #[default]
+    Float,
+    /// Literals outside of units become `Token::Decimal`, which does not lose digits past the precision of a float
+    Decimal,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TokenizeError {
     ParseFloatError(std::num::ParseFloatError)
 }
 
+/// A range of character offsets `start..end` in the tokenized line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// Returns the smallest span covering both `self` and `other`
+    pub fn merge(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
 // This can be done in parallel for each line
 pub fn tokenize(line: &str) -> Result<Vec<Token>, TokenizeError> {
-    let mut out_vec = Vec::<Token>::new();
+    Ok(tokenize_spanned(line)?.into_iter().map(|x| x.token).collect())
+}
+
+/// Tokenizes the line, keeping the position of every token in it
+pub fn tokenize_spanned(line: &str) -> Result<Vec<SpannedToken>, TokenizeError> {
+    tokenize_spanned_with(line, NumberLiterals::Float)
+}
+
+/// Tokenizes the line, reading number literals as specified
+pub fn tokenize_spanned_with(line: &str, literals: NumberLiterals) -> Result<Vec<SpannedToken>, TokenizeError> {
+    let mut out_vec = Vec::<SpannedToken>::new();
     let mut current = String::new();
+    let mut current_start = 0;
     let mut is_num = true;
+    // Numbers in units are exponents, they stay floats
+    let mut in_unit = false;
+
+    let chars: Vec<char> = line.chars().collect();
+    let mut skip = false;
+
+    for (i, &char) in chars.iter().enumerate() {
+        // The second character of a two character comparison, ex. <=, or the sign of an exponent
+        if std::mem::take(&mut skip) {
+            continue;
+        }
+        let followed_by_equals = chars.get(i + 1) == Some(&'=');
+        let single = match char {
+            '<' if followed_by_equals => Some(Token::Comparison(Comparison::LessEqual)),
+            '>' if followed_by_equals => Some(Token::Comparison(Comparison::GreaterEqual)),
+            '!' if followed_by_equals => Some(Token::Comparison(Comparison::NotEqual)),
+            '<' => Some(Token::Comparison(Comparison::Less)),
+            '>' => Some(Token::Comparison(Comparison::Greater)),
+            '=' => Some(Token::Equals),
+            '+' => Some(Token::Operation(Operation::Add)),
+            '-' => Some(Token::Operation(Operation::Sub)),
+            '*' => Some(Token::Operation(Operation::Mul)),
+            '/' => Some(Token::Operation(Operation::Div)),
+            '^' => Some(Token::Operation(Operation::Exp)),
+            '(' => Some(Token::OpeningParen),
+            ')' => Some(Token::ClosingParen),
+            '[' => Some(Token::OpeningBracket),
+            ']' => Some(Token::ClosingBracket),
+            ',' => Some(Token::Comma),
+            _ => None,
+        };
 
-    for char in line.chars() {
         match char {
-            '=' => {
-                if !current.is_empty() {
-                    out_vec.push(parse_token(&current, is_num)?);
-                    current.clear();
-                }
-                out_vec.push(Token::Equals);
-                is_num = true;
-            }
-            '+' => {
-                if !current.is_empty() {
-                    out_vec.push(parse_token(&current, is_num)?);
-                    current.clear();
-                }
-                out_vec.push(Token::Operation(Operation::Add));
-                is_num = true;
-            }
-            '-' => {
-                if !current.is_empty() {
-                    out_vec.push(parse_token(&current, is_num)?);
-                    current.clear();
-                }
-                out_vec.push(Token::Operation(Operation::Sub));
-                is_num = true;
-            }
-            '*' => {
-                if !current.is_empty() {
-                    out_vec.push(parse_token(&current, is_num)?);
-                    current.clear();
-                }
-                out_vec.push(Token::Operation(Operation::Mul));
-                is_num = true;
-            }
-            '/' => {
-                if !current.is_empty() {
-                    out_vec.push(parse_token(&current, is_num)?);
-                    current.clear();
-                }
-                out_vec.push(Token::Operation(Operation::Div));
-                is_num = true;
-            }
-            '^' => {
-                if !current.is_empty() {
-                    out_vec.push(parse_token(&current, is_num)?);
-                    current.clear();
-                }
-                out_vec.push(Token::Operation(Operation::Exp));
-                is_num = true;
-            }
-            '(' => {
-                if !current.is_empty() {
-                    out_vec.push(parse_token(&current, is_num)?);
-                    current.clear();
-                }
-                out_vec.push(Token::OpeningParen);
-                is_num = true;
-            }
-            ')' => {
-                if !current.is_empty() {
-                    out_vec.push(parse_token(&current, is_num)?);
-                    current.clear();
-                }
-                out_vec.push(Token::ClosingParen);
-                is_num = true;
-            }
-            '[' => {
+            _ if single.is_some() || char.is_whitespace() => {
                 if !current.is_empty() {
-                    out_vec.push(parse_token(&current, is_num)?);
+                    out_vec.push(SpannedToken {
+                        token: read_token(&current, is_num, literals, in_unit)?,
+                        span: Span::new(current_start, i),
+                    });
                     current.clear();
                 }
-                out_vec.push(Token::OpeningBracket);
-                is_num = true;
-            }
-            ']' => {
-                if !current.is_empty() {
-                    out_vec.push(parse_token(&current, is_num)?);
-                    current.clear();
-                }
-                out_vec.push(Token::ClosingBracket);
-                is_num = true;
-            }
-            ',' => {
-                if !current.is_empty() {
-                    out_vec.push(parse_token(&current, is_num)?);
-                    current.clear();
+                if let Some(token) = single {
+                    let len = match token {
+                        Token::OpeningBracket => { in_unit = true; 1 },
+                        Token::ClosingBracket => { in_unit = false; 1 },
+                        Token::Comparison(Comparison::LessEqual | Comparison::GreaterEqual | Comparison::NotEqual) => { skip = true; 2 },
+                        _ => 1,
+                    };
+                    out_vec.push(SpannedToken { token, span: Span::new(i, i + len) });
                 }
-                out_vec.push(Token::Comma);
                 is_num = true;
             }
-            _ if char.is_whitespace() => {
-                if !current.is_empty() {
-                    out_vec.push(parse_token(&current, is_num)?);
-                    current.clear();
+            '0'..='9' | '.' => {
+                if current.is_empty() {
+                    current_start = i;
                 }
-                // out_vec.push(Token::Equals);
-                is_num = true;
-            }
-            '0'..='9' => {
                 current.push(char);
             }
-            '.' => {
+            // Scientific notation, ex. 1e-3 or 6.02e23, which is how large and small results are printed
+            'e' | 'E' if is_num && !current.is_empty() && !current.contains(['e', 'E']) && exponent_follows(&chars[(i + 1)..]) => {
                 current.push(char);
+                if let Some(&sign @ ('+' | '-')) = chars.get(i + 1) {
+                    current.push(sign);
+                    skip = true;
+                }
             }
             '#' => {
                 break;
             }
             _ => {
                 if is_num && !current.is_empty() {
-                    out_vec.push(parse_token(&current, is_num)?);
+                    out_vec.push(SpannedToken {
+                        token: read_token(&current, is_num, literals, in_unit)?,
+                        span: Span::new(current_start, i),
+                    });
                     current.clear();
                 }
+                if current.is_empty() {
+                    current_start = i;
+                }
                 current.push(char);
                 is_num = false;
             }
         }
     }
     if !current.is_empty() {
-        out_vec.push(parse_token(&current, is_num)?);
-        // current.clear();
+        out_vec.push(SpannedToken {
+            token: read_token(&current, is_num, literals, in_unit)?,
+            span: Span::new(current_start, current_start + current.chars().count()),
+        });
     }
 
     Ok(out_vec)
 }
 
+/// Whether the characters after an e are the exponent of a number, ex. 3 or -3 in 1e-3
+fn exponent_follows(rest: &[char]) -> bool {
+    match rest {
+        ['+' | '-', digit, ..] | [digit, ..] => digit.is_ascii_digit(),
+        [] => false,
+    }
+}
+
+fn read_token(to_tokenize: &str, is_num: bool, literals: NumberLiterals, in_unit: bool) -> Result<Token, TokenizeError> {
+    let token = parse_token(to_tokenize, is_num)?;
+    match token {
+        // The literal is already known to be a valid float, so it is a valid decimal
+        Token::Number(_) if literals == NumberLiterals::Decimal && !in_unit => Ok(Token::Decimal(to_tokenize.parse().unwrap())),
+        _ => Ok(token),
+    }
+}
+
 fn parse_token(to_tokenize: &str, is_num: bool) -> Result<Token, TokenizeError> {
     if is_num {
         // turbofish pog
@@ -165,6 +270,13 @@ fn parse_token(to_tokenize: &str, is_num: bool) -> Result<Token, TokenizeError>
     } else {
         match to_tokenize {
             "let" => Ok(Token::Let),
+            "to" | "in" => Ok(Token::Conversion),
+            "true" => Ok(Token::Boolean(true)),
+            "false" => Ok(Token::Boolean(false)),
+            "and" => Ok(Token::Logic(Logic::And)),
+            "or" => Ok(Token::Logic(Logic::Or)),
+            "xor" => Ok(Token::Logic(Logic::Xor)),
+            "not" => Ok(Token::Logic(Logic::Not)),
             _ => Ok(Token::Name(to_tokenize.into()))
         }
     }
@@ -182,6 +294,10 @@ mod tests {
         assert_eq!(parse_token("33", true).unwrap(), Token::Number(33.0));
 
         assert!(parse_token("thisisdefinetelynotanumber", true).is_err());
+
+        assert_eq!(parse_token("true", false).unwrap(), Token::Boolean(true));
+
+        assert_eq!(parse_token("xor", false).unwrap(), Token::Logic(Logic::Xor));
     }
 
     #[test]
@@ -227,6 +343,77 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tokenize_decimal_literals() {
+        let tokens: Vec<Token> = tokenize_spanned_with("12345678901234567.89 [m^2]", NumberLiterals::Decimal).unwrap()
+            .into_iter()
+            .map(|x| x.token)
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Decimal("12345678901234567.89".parse().unwrap()),
+                Token::OpeningBracket,
+                Token::Name("m".into()),
+                Token::Operation(Operation::Exp),
+                // Exponents of units stay floats
+                Token::Number(2.0),
+                Token::ClosingBracket,
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_spanned_tracks_positions() {
+        let tokens = tokenize_spanned("x1 = 10.5/y").unwrap();
+        assert_eq!(
+            tokens.iter().map(|x| x.span).collect::<Vec<Span>>(),
+            vec![
+                Span::new(0, 2),
+                Span::new(3, 4),
+                Span::new(5, 9),
+                Span::new(9, 10),
+                Span::new(10, 11),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_scientific_notation() {
+        let tokens = tokenize_spanned("1e-3 + 2.5E+2 - 2e").unwrap();
+        assert_eq!(
+            tokens.iter().map(|x| x.token.clone()).collect::<Vec<Token>>(),
+            vec![
+                Token::Number(0.001),
+                Token::Operation(Operation::Add),
+                Token::Number(250.),
+                Token::Operation(Operation::Sub),
+                Token::Number(2.),
+                Token::Name("e".into()),
+            ]
+        );
+        assert_eq!(tokens[2].span, Span::new(7, 13));
+    }
+
+    #[test]
+    fn tokenize_comparisons() {
+        let tokens = tokenize_spanned("x<=3 != y>2").unwrap();
+        assert_eq!(
+            tokens.iter().map(|x| x.token.clone()).collect::<Vec<Token>>(),
+            vec![
+                Token::Name("x".into()),
+                Token::Comparison(Comparison::LessEqual),
+                Token::Number(3.0),
+                Token::Comparison(Comparison::NotEqual),
+                Token::Name("y".into()),
+                Token::Comparison(Comparison::Greater),
+                Token::Number(2.0),
+            ]
+        );
+        assert_eq!(tokens[1].span, Span::new(1, 3));
+        assert_eq!(tokens[3].span, Span::new(5, 7));
+    }
+
     #[test]
     fn tokenize_whitespace_returns_nothing() {
         let x = vec![
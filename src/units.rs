@@ -0,0 +1,478 @@
+use std::fmt;
+use std::ops::{Div, Mul};
+
+use crate::parser::{ParseError, node::{ASTNode, ASTNodeType}};
+use crate::tokenizer::{Operation, Token};
+
+/// Symbols of the SI base units, in the order of `Dimension` exponents
+pub static BASE_UNITS: [&str; 7] = ["m", "kg", "s", "A", "K", "mol", "cd"];
+
+// Order in which base units are printed, ex. kg*m/s^2
+static DISPLAY_ORDER: [usize; 7] = [1, 0, 2, 3, 4, 5, 6];
+
+const DIMENSIONLESS: Dimension = Dimension([0, 0, 0, 0, 0, 0, 0]);
+const LENGTH: Dimension = Dimension([1, 0, 0, 0, 0, 0, 0]);
+const MASS: Dimension = Dimension([0, 1, 0, 0, 0, 0, 0]);
+const TIME: Dimension = Dimension([0, 0, 1, 0, 0, 0, 0]);
+const AREA: Dimension = Dimension([2, 0, 0, 0, 0, 0, 0]);
+const VOLUME: Dimension = Dimension([3, 0, 0, 0, 0, 0, 0]);
+const VELOCITY: Dimension = Dimension([1, 0, -1, 0, 0, 0, 0]);
+const FREQUENCY: Dimension = Dimension([0, 0, -1, 0, 0, 0, 0]);
+const FORCE: Dimension = Dimension([1, 1, -2, 0, 0, 0, 0]);
+const PRESSURE: Dimension = Dimension([-1, 1, -2, 0, 0, 0, 0]);
+const ENERGY: Dimension = Dimension([2, 1, -2, 0, 0, 0, 0]);
+const POWER: Dimension = Dimension([2, 1, -3, 0, 0, 0, 0]);
+const CHARGE: Dimension = Dimension([0, 0, 1, 1, 0, 0, 0]);
+const VOLTAGE: Dimension = Dimension([2, 1, -3, -1, 0, 0, 0]);
+const CAPACITANCE: Dimension = Dimension([-2, -1, 4, 2, 0, 0, 0]);
+const RESISTANCE: Dimension = Dimension([2, 1, -3, -2, 0, 0, 0]);
+const MAGNETIC_FLUX_DENSITY: Dimension = Dimension([0, 1, -2, -1, 0, 0, 0]);
+const MAGNETIC_FLUX: Dimension = Dimension([2, 1, -2, -1, 0, 0, 0]);
+const INDUCTANCE: Dimension = Dimension([2, 1, -2, -2, 0, 0, 0]);
+
+/// Derived units which are used when printing a dimension that matches them exactly
+static DERIVED_UNITS: &[(&str, Dimension)] = &[
+    ("N", FORCE),
+    ("Pa", PRESSURE),
+    ("J", ENERGY),
+    ("W", POWER),
+    ("C", CHARGE),
+    ("V", VOLTAGE),
+    ("F", CAPACITANCE),
+    ("ohm", RESISTANCE),
+    ("T", MAGNETIC_FLUX_DENSITY),
+    ("Wb", MAGNETIC_FLUX),
+    ("H", INDUCTANCE),
+];
+
+/// Every known unit: symbol, value in SI base units, dimension and whether it takes SI prefixes
+static UNITS: &[(&str, f64, Dimension, bool)] = &[
+    // SI base units, kg is prefixed through g
+    ("m", 1., LENGTH, true),
+    ("kg", 1., MASS, false),
+    ("g", 1e-3, MASS, true),
+    ("s", 1., TIME, true),
+    ("A", 1., Dimension([0, 0, 0, 1, 0, 0, 0]), true),
+    ("K", 1., Dimension([0, 0, 0, 0, 1, 0, 0]), true),
+    ("mol", 1., Dimension([0, 0, 0, 0, 0, 1, 0]), true),
+    ("cd", 1., Dimension([0, 0, 0, 0, 0, 0, 1]), true),
+    // SI derived units
+    ("Hz", 1., FREQUENCY, true),
+    ("N", 1., FORCE, true),
+    ("Pa", 1., PRESSURE, true),
+    ("J", 1., ENERGY, true),
+    ("W", 1., POWER, true),
+    ("C", 1., CHARGE, true),
+    ("V", 1., VOLTAGE, true),
+    ("F", 1., CAPACITANCE, true),
+    ("ohm", 1., RESISTANCE, true),
+    ("T", 1., MAGNETIC_FLUX_DENSITY, true),
+    ("Wb", 1., MAGNETIC_FLUX, true),
+    ("H", 1., INDUCTANCE, true),
+    ("rad", 1., DIMENSIONLESS, false),
+    // Non-SI units
+    ("deg", std::f64::consts::PI / 180., DIMENSIONLESS, false),
+    ("min", 60., TIME, false),
+    ("h", 3600., TIME, false),
+    ("day", 86400., TIME, false),
+    ("week", 604800., TIME, false),
+    ("yr", 31557600., TIME, false), // julian year
+    ("inch", 0.0254, LENGTH, false),
+    ("ft", 0.3048, LENGTH, false),
+    ("yd", 0.9144, LENGTH, false),
+    ("mi", 1609.344, LENGTH, false),
+    ("nmi", 1852., LENGTH, false),
+    ("au", 149597870700., LENGTH, false),
+    ("ha", 1e4, AREA, false),
+    ("L", 1e-3, VOLUME, true),
+    ("gal", 3.785411784e-3, VOLUME, false), // US gallon
+    ("t", 1e3, MASS, false),
+    ("lb", 0.45359237, MASS, false),
+    ("oz", 0.028349523125, MASS, false),
+    ("mph", 0.44704, VELOCITY, false),
+    ("kn", 1852. / 3600., VELOCITY, false),
+    ("eV", 1.602176634e-19, ENERGY, true),
+    ("cal", 4.184, ENERGY, true),
+    ("Wh", 3600., ENERGY, true),
+    ("bar", 1e5, PRESSURE, true),
+    ("atm", 101325., PRESSURE, false),
+    ("psi", 6894.757293168, PRESSURE, false),
+    ("hp", 745.6998715822702, POWER, false), // mechanical horsepower
+];
+
+/// SI prefixes and their multipliers
+static PREFIXES: &[(&str, f64)] = &[
+    ("Q", 1e30), ("R", 1e27), ("Y", 1e24), ("Z", 1e21), ("E", 1e18), ("P", 1e15), ("T", 1e12),
+    ("G", 1e9), ("M", 1e6), ("k", 1e3), ("h", 1e2), ("da", 1e1), ("d", 1e-1), ("c", 1e-2),
+    ("m", 1e-3), ("u", 1e-6), ("µ", 1e-6), ("n", 1e-9), ("p", 1e-12), ("f", 1e-15), ("a", 1e-18),
+    ("z", 1e-21), ("y", 1e-24), ("r", 1e-27), ("q", 1e-30),
+];
+
+/// Exponents of the SI base units (m, kg, s, A, K, mol, cd)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Dimension(pub [i32; 7]);
+
+impl Dimension {
+    pub fn dimensionless() -> Self {
+        Dimension([0; 7])
+    }
+
+    /// Dimension of the n-th base unit
+    pub fn base(n: usize) -> Self {
+        let mut exponents = [0; 7];
+        exponents[n] = 1;
+        Dimension(exponents)
+    }
+
+    pub fn is_dimensionless(&self) -> bool {
+        self.0.iter().all(|x| *x == 0)
+    }
+
+    pub fn powi(self, exp: i32) -> Self {
+        let mut out = self;
+        for a in out.0.iter_mut() {
+            *a *= exp;
+        }
+        out
+    }
+
+    /// Raises the dimension to a real power, which only works if all exponents stay integers
+    pub fn powf(self, exp: f64) -> Option<Self> {
+        let mut out = self;
+        for a in out.0.iter_mut() {
+            let new = *a as f64 * exp;
+            if new.fract() != 0. {
+                return None;
+            }
+            *a = new as i32;
+        }
+        Some(out)
+    }
+}
+
+impl Mul for Dimension {
+    type Output = Dimension;
+
+    // Multiplying units adds their exponents
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, other: Dimension) -> Dimension {
+        let mut out = self;
+        for (a, b) in out.0.iter_mut().zip(other.0.iter()) {
+            *a += b;
+        }
+        out
+    }
+}
+
+impl Div for Dimension {
+    type Output = Dimension;
+
+    fn div(self, other: Dimension) -> Dimension {
+        self * other.powi(-1)
+    }
+}
+
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_dimensionless() {
+            return write!(f, "1");
+        }
+        if let Some((name, _)) = DERIVED_UNITS.iter().find(|(_, dim)| dim == self) {
+            return write!(f, "{}", name);
+        }
+
+        let format_part = |positive: bool| {
+            let parts: Vec<String> = DISPLAY_ORDER
+                .iter()
+                .filter(|&&i| if positive { self.0[i] > 0 } else { self.0[i] < 0 })
+                .map(|&i| match self.0[i].abs() {
+                    1 => BASE_UNITS[i].to_string(),
+                    exp => format!("{}^{}", BASE_UNITS[i], exp),
+                })
+                .collect();
+            (parts.len(), parts.join("*"))
+        };
+
+        let (num_count, numerator) = format_part(true);
+        let (den_count, denominator) = format_part(false);
+
+        let numerator = if num_count == 0 { "1".to_string() } else { numerator };
+        match den_count {
+            0 => write!(f, "{}", numerator),
+            1 => write!(f, "{}/{}", numerator, denominator),
+            _ => write!(f, "{}/({})", numerator, denominator),
+        }
+    }
+}
+
+/// A unit of measure, as written in brackets, ex. [m/s^2]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    /// The unit as written by the user
+    pub name: String,
+    /// What one of this unit is in the SI base units
+    pub scale: f64,
+    pub dimension: Dimension,
+}
+
+impl Unit {
+    pub fn new(name: &str, scale: f64, dimension: Dimension) -> Self {
+        Unit { name: name.into(), scale, dimension }
+    }
+
+    pub fn dimensionless() -> Self {
+        Unit::new("1", 1., Dimension::dimensionless())
+    }
+
+    pub fn powi(&self, exp: i32) -> Self {
+        Unit::new(
+            &format!("{}^{}", self.name, exp),
+            self.scale.powi(exp),
+            self.dimension.powi(exp)
+        )
+    }
+
+    /// Expresses a quantity in this unit, ex. 1000 m as 1 km
+    pub fn format(&self, quantity: Quantity) -> String {
+        format!("{} {}", format_number(quantity.value / self.scale), self.name)
+    }
+}
+
+impl Mul for Unit {
+    type Output = Unit;
+
+    fn mul(self, other: Unit) -> Unit {
+        Unit::new(
+            &format!("{}*{}", self.name, other.name),
+            self.scale * other.scale,
+            self.dimension * other.dimension
+        )
+    }
+}
+
+impl Div for Unit {
+    type Output = Unit;
+
+    fn div(self, other: Unit) -> Unit {
+        Unit::new(
+            &format!("{}/{}", self.name, other.name),
+            self.scale / other.scale,
+            self.dimension / other.dimension
+        )
+    }
+}
+
+/// A value together with its dimension, always stored in the SI base units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub dimension: Dimension,
+}
+
+impl Quantity {
+    pub fn new(value: f64, dimension: Dimension) -> Self {
+        Quantity { value, dimension }
+    }
+
+    pub fn number(value: f64) -> Self {
+        Quantity::new(value, Dimension::dimensionless())
+    }
+
+    pub fn from_unit(unit: &Unit) -> Self {
+        Quantity::new(unit.scale, unit.dimension)
+    }
+
+    pub fn checked_add(self, other: Quantity) -> Result<Quantity, String> {
+        if self.dimension != other.dimension {
+            return Err(format!("Cannot add [{}] and [{}]", self.dimension, other.dimension));
+        }
+        Ok(Quantity::new(self.value + other.value, self.dimension))
+    }
+
+    pub fn checked_sub(self, other: Quantity) -> Result<Quantity, String> {
+        if self.dimension != other.dimension {
+            return Err(format!("Cannot subtract [{}] from [{}]", other.dimension, self.dimension));
+        }
+        Ok(Quantity::new(self.value - other.value, self.dimension))
+    }
+
+    pub fn checked_pow(self, other: Quantity) -> Result<Quantity, String> {
+        if !other.dimension.is_dimensionless() {
+            return Err(format!("Exponent must be dimensionless, found [{}]", other.dimension));
+        }
+        match self.dimension.powf(other.value) {
+            Some(dimension) => Ok(Quantity::new(f64::powf(self.value, other.value), dimension)),
+            None => Err(format!("Cannot raise [{}] to the power of {}", self.dimension, other.value)),
+        }
+    }
+}
+
+impl Mul for Quantity {
+    type Output = Quantity;
+
+    fn mul(self, other: Quantity) -> Quantity {
+        Quantity::new(self.value * other.value, self.dimension * other.dimension)
+    }
+}
+
+impl Div for Quantity {
+    type Output = Quantity;
+
+    fn div(self, other: Quantity) -> Quantity {
+        Quantity::new(self.value / other.value, self.dimension / other.dimension)
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.dimension.is_dimensionless() {
+            write!(f, "{}", format_number(self.value))
+        } else {
+            write!(f, "{} {}", format_number(self.value), self.dimension)
+        }
+    }
+}
+
+/// Formats very large and very small numbers in scientific notation, ex. 6.62607015e-34
+pub fn format_number(value: f64) -> String {
+    if value != 0. && value.is_finite() && (value.abs() >= 1e16 || value.abs() < 1e-6) {
+        format!("{:e}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Finds a unit by its symbol, which can start with an SI prefix (ex. km, kWh, µs)
+pub fn lookup_unit(name: &str) -> Option<Unit> {
+    let find = |symbol: &str| UNITS.iter().find(|(x, ..)| *x == symbol);
+
+    if let Some((_, scale, dimension, _)) = find(name) {
+        return Some(Unit::new(name, *scale, *dimension));
+    }
+    PREFIXES
+        .iter()
+        .filter_map(|(prefix, multiplier)| {
+            match find(name.strip_prefix(prefix)?) {
+                Some((_, scale, dimension, true)) => Some(Unit::new(name, multiplier * scale, *dimension)),
+                _ => None
+            }
+        })
+        .next()
+}
+
+/// Parses the contents of unit brackets, ex. the `m/s^2` in `10 [m/s^2]`.
+///
+/// Units can be multiplied (explicitly or by juxtaposition), divided and raised to integer powers.
+/// Parens inside the brackets have already been grouped into empty nodes by `parse_parens`.
+pub fn parse_unit(nodes: &[ASTNode]) -> Result<Unit, ParseError> {
+    let mut out: Option<Unit> = None;
+    let mut divide = false;
+    let mut i = 0;
+
+    while i < nodes.len() {
+        let mut factor = match &nodes[i].node_type {
+            ASTNodeType::Delimeter(Token::Name(name)) => lookup_unit(name).ok_or_else(|| ParseError::UnknownUnit(name.clone()))?,
+            ASTNodeType::Delimeter(Token::Number(num)) if *num == 1. => Unit::dimensionless(),
+            ASTNodeType::Empty => {
+                let inner = parse_unit(&nodes[i].children)?;
+                Unit::new(&format!("({})", inner.name), inner.scale, inner.dimension)
+            }
+            ASTNodeType::Delimeter(Token::Operation(Operation::Mul)) if out.is_some() => {
+                i += 1;
+                continue;
+            }
+            ASTNodeType::Delimeter(Token::Operation(Operation::Div)) if out.is_some() && !divide => {
+                divide = true;
+                i += 1;
+                continue;
+            }
+            _ => return Err(ParseError::InvalidUnit),
+        };
+
+        // Exponent, possibly negative
+        if let Some(ASTNodeType::Delimeter(Token::Operation(Operation::Exp))) = nodes.get(i + 1).map(|x| &x.node_type) {
+            let (sign, exp_index) = match nodes.get(i + 2).map(|x| &x.node_type) {
+                Some(ASTNodeType::Delimeter(Token::Operation(Operation::Sub))) => (-1, i + 3),
+                _ => (1, i + 2),
+            };
+            match nodes.get(exp_index).map(|x| &x.node_type) {
+                Some(ASTNodeType::Delimeter(Token::Number(exp))) if exp.fract() == 0. => {
+                    factor = factor.powi(sign * *exp as i32);
+                }
+                _ => return Err(ParseError::InvalidUnit),
+            }
+            i = exp_index;
+        }
+
+        out = Some(match out {
+            None => factor,
+            Some(unit) if divide => unit / factor,
+            Some(unit) => unit * factor,
+        });
+        divide = false;
+        i += 1;
+    }
+
+    if divide {
+        return Err(ParseError::InvalidUnit);
+    }
+    out.ok_or(ParseError::InvalidUnit)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::parsers::{parse_parens, wrap_tokens}, tokenizer::tokenize};
+
+    use super::*;
+
+    fn unit_of(text: &str) -> Result<Unit, ParseError> {
+        let tree = parse_parens(&mut wrap_tokens(&tokenize(text).unwrap())).unwrap();
+        parse_unit(&tree.children)
+    }
+
+    #[test]
+    fn parse_unit_acceleration() {
+        let unit = unit_of("m/s^2").unwrap();
+        assert_eq!(unit.dimension, Dimension([1, 0, -2, 0, 0, 0, 0]));
+        assert_eq!(unit.name, "m/s^2");
+    }
+
+    #[test]
+    fn parse_unit_juxtaposition_and_parens() {
+        assert_eq!(unit_of("kg m/(s^2)").unwrap().dimension, lookup_unit("N").unwrap().dimension);
+        assert_eq!(unit_of("s^-1").unwrap().dimension, Dimension([0, 0, -1, 0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn parse_unit_errors() {
+        assert_eq!(unit_of("parsec"), Err(ParseError::UnknownUnit("parsec".into())));
+        assert_eq!(unit_of("m/"), Err(ParseError::InvalidUnit));
+        assert_eq!(unit_of("m^x"), Err(ParseError::InvalidUnit));
+    }
+
+    #[test]
+    fn lookup_unit_prefixes() {
+        assert_eq!(lookup_unit("km"), Some(Unit::new("km", 1e3, LENGTH)));
+        assert_eq!(lookup_unit("mg"), Some(Unit::new("mg", 1e-6, MASS)));
+        assert_eq!(lookup_unit("kWh"), Some(Unit::new("kWh", 3.6e6, ENERGY)));
+        assert_eq!(lookup_unit("min").map(|x| x.scale), Some(60.));
+        assert_eq!(lookup_unit("kkg"), None);
+        assert_eq!(lookup_unit("kmi"), None);
+    }
+
+    #[test]
+    fn dimension_display() {
+        assert_eq!(Dimension([1, 0, -2, 0, 0, 0, 0]).to_string(), "m/s^2");
+        assert_eq!(Dimension([2, 1, -2, 0, 0, 0, 0]).to_string(), "J");
+        assert_eq!(Dimension([0, 0, -1, 0, 0, 0, 0]).to_string(), "1/s");
+        assert_eq!(Dimension([1, 0, -1, 0, -1, 0, 0]).to_string(), "m/(s*K)");
+    }
+
+    #[test]
+    fn format_number_uses_scientific_notation_for_extremes() {
+        assert_eq!(format_number(1500.), "1500");
+        assert_eq!(format_number(0.25), "0.25");
+        assert_eq!(format_number(6.62607015e-34), "6.62607015e-34");
+        assert_eq!(format_number(6.02214076e23), "6.02214076e23");
+    }
+}
@@ -1,5 +1,5 @@
 mod tests {
-    use l_robot::{parser::{node::{ASTNode, ASTNodeType}, parsers::parse}, resolver::{Resolver, resolve_message::{ResolveMessage, ResolveMessageType}}, tokenizer::{Token, tokenize}};
+    use l_robot::{document::Document, parser::{ParseError, node::{ASTNode, ASTNodeType}, parsers::{parse, parse_spanned}}, resolve_lines_with, resolver::{DEFAULT_DECIMAL_PRECISION, MAX_RECURSION_LIMIT, NumberMode, RationalFormat, Resolver, RootPolicy, Tolerance, namespace::NamespaceElement, resolve_message::{ResolveMessage, ResolveMessageType}}, tokenizer::{Span, Token, tokenize, tokenize_spanned}};
 
     #[test]
     fn tokenize_parse_x_squared() {
@@ -104,7 +104,7 @@ mod tests {
     #[test]
     fn full_egyptian_triangle() {
         // Egyptian triangle
-        let x = vec![
+        let x = [
             "x = 3",
             "y = 4",
             "z = (x^2 + y^2)^1/2",
@@ -123,7 +123,7 @@ mod tests {
 
     #[test]
     fn full_fraction_subtraction() {
-        let x = vec![
+        let x = [
             "x = 0.0002",
             "y = 0.0001",
             "x - y",
@@ -216,4 +216,1137 @@ mod tests {
             );
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn divide_by_zero_points_at_quotient() {
+        let x = "y = 3 + 10/(2 - 2)";
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve_line(parse_spanned(&tokenize_spanned(x).unwrap()).unwrap());
+
+        assert_eq!(output.len(), 1);
+        assert_eq!(output[0].content, "Divide by zero");
+        assert_eq!(output[0].span, Some(Span::new(8, 18)));
+    }
+
+    #[test]
+    fn unknown_name_in_fn_body_points_at_name() {
+        let x = "let f(a) = a + b";
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve_line(parse_spanned(&tokenize_spanned(x).unwrap()).unwrap());
+
+        assert_eq!(output, vec![ResolveMessage::error("Unknown name: b").with_span(Some(Span::new(15, 16)))]);
+    }
+
+    #[test]
+    fn full_units_derived() {
+        let x = [
+            "m_1 = 2 [kg]",
+            "a = 10 [m/s^2]",
+            "F = m_1 * a",
+            "F / 4 [N]",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("m_1 = 2 kg")),
+            (1, ResolveMessage::output("a = 10 m/s^2")),
+            (2, ResolveMessage::output("F = 20 N")),
+            (3, ResolveMessage::output("? = 5")),
+        ]);
+    }
+
+    #[test]
+    fn full_units_incompatible_sum() {
+        let x = "3 [m] + 2 [s]";
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve_line(parse(&tokenize(x).unwrap()).unwrap());
+
+        assert_eq!(output, vec![ResolveMessage::error("Cannot add [m] and [s]")]);
+    }
+
+    #[test]
+    fn full_units_unknown_unit() {
+        assert_eq!(parse(&tokenize("3 [furlong]").unwrap()), Err(ParseError::UnknownUnit("furlong".into())));
+        assert_eq!(parse(&tokenize("3 [m").unwrap()), Err(ParseError::UnmatchedOpeningBracket));
+    }
+
+    #[test]
+    fn full_unit_conversion() {
+        let x = [
+            "x = 3 [km] to [mi]",
+            "5 [kJ] in [kWh]",
+            "x + 1 [m]",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("x = 1.8641135767120018 mi")),
+            (1, ResolveMessage::output("? = 0.001388888888888889 kWh")),
+            (2, ResolveMessage::output("? = 3001 m")),
+        ]);
+    }
+
+    #[test]
+    fn full_unit_conversion_incompatible() {
+        let x = "3 [m] to [s]";
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve_line(parse(&tokenize(x).unwrap()).unwrap());
+
+        assert_eq!(output, vec![ResolveMessage::error("Cannot convert [m] to [s]")]);
+    }
+
+    #[test]
+    fn full_builtin_functions() {
+        let x = [
+            "sqrt(16) + max(1, 7, 3)",
+            "sqrt(-1)",
+            "sin(1, 2)",
+            "atan2(1, 1) to [deg]",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("? = 11")),
+            (1, ResolveMessage::output("? = i")),
+            (2, ResolveMessage::error("sin expects 1 argument, got 2")),
+            (3, ResolveMessage::output("? = 45 deg")),
+        ]);
+    }
+
+    #[test]
+    fn full_user_function_shadows_builtin() {
+        let x = [
+            "let sqrt(x) = x + 1",
+            "sqrt(3)",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output[1], (1, ResolveMessage::output("? = 4")));
+    }
+
+    #[test]
+    fn full_constants() {
+        let x = [
+            "2 * pi",
+            "tau = 6",
+            "let e(x) = x",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("? = 6.283185307179586")),
+            (1, ResolveMessage::error("Cannot reassign constant tau")),
+            (2, ResolveMessage::error("Cannot reassign constant e")),
+        ]);
+    }
+
+    #[test]
+    fn full_physics_constants() {
+        let x = "E = 1 [kg] * c^2";
+        let mut resolver = Resolver::new().with_physics_constants();
+
+        let output = resolver.resolve_line(parse(&tokenize(x).unwrap()).unwrap());
+
+        assert_eq!(output, vec![ResolveMessage::output("E = 8.987551787368176e16 J")]);
+    }
+
+    #[test]
+    fn tokenize_parse_coefficients() {
+        let x = parse(&tokenize("2x^2 + 3(x + 1)").unwrap()).unwrap();
+
+        assert_eq!(
+            x,
+            ASTNode::new(ASTNodeType::Sum, vec![
+                ASTNode::new(ASTNodeType::Product, vec![
+                    ASTNode::number(2.),
+                    ASTNode::new(ASTNodeType::Power, vec![
+                        ASTNode::delimeter(Token::Name("x".into())),
+                        ASTNode::number(2.),
+                    ]),
+                ]),
+                ASTNode::new(ASTNodeType::Product, vec![
+                    ASTNode::number(3.),
+                    ASTNode::new(ASTNodeType::Sum, vec![
+                        ASTNode::delimeter(Token::Name("x".into())),
+                        ASTNode::number(1.),
+                    ]),
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn full_linear_equations() {
+        let x = [
+            "2x + x = 9",
+            "y / 3 = y - 4",
+            "z + 1 = z + 2",
+            "2w - w = w",
+            "3 [m] + d = 2d to [cm]",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("x = 3")),
+            (1, ResolveMessage::output("y = 6")),
+            (2, ResolveMessage::error("Equation has no solution")),
+            (3, ResolveMessage::info("Equation has infinitely many solutions, it is true for every w")),
+            (4, ResolveMessage::output("d = 300 cm")),
+        ]);
+    }
+
+    #[test]
+    fn tokenize_parse_sum_and_difference_left_to_right() {
+        let x = parse(&tokenize("10 - 2 + 3 = -1").unwrap()).unwrap();
+
+        assert_eq!(
+            x,
+            ASTNode::new(ASTNodeType::Equality, vec![
+                ASTNode::new(ASTNodeType::Sum, vec![
+                    ASTNode::new(ASTNodeType::Difference, vec![
+                        ASTNode::number(10.),
+                        ASTNode::number(2.),
+                    ]),
+                    ASTNode::number(3.),
+                ]),
+                ASTNode::number(-1.),
+            ])
+        );
+    }
+
+    #[test]
+    fn full_polynomial_equations() {
+        let x = [
+            "x^2 - x = 6",
+            "y^3 - 6y^2 + 11y - 6 = 0",
+            "z^2 = -1",
+            "t^2 = 9 [s^2]",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("x = -2, 3")),
+            (0, ResolveMessage::info("x has 2 solutions, so it was not assigned")),
+            (1, ResolveMessage::output("y = 1, 2, 3")),
+            (1, ResolveMessage::info("y has 3 solutions, so it was not assigned")),
+            (2, ResolveMessage::error("Equation has no real solution")),
+            (3, ResolveMessage::output("t = -3 s, 3 s")),
+            (3, ResolveMessage::info("t has 2 solutions, so it was not assigned")),
+        ]);
+        assert_eq!(resolver.namespace.get("x"), None);
+    }
+
+    #[test]
+    fn full_polynomial_root_policy() {
+        let x = "x^2 - x = 6";
+        let mut resolver = Resolver::new();
+        resolver.root_policy = RootPolicy::Largest;
+
+        let output = resolver.resolve_line(parse(&tokenize(x).unwrap()).unwrap());
+
+        assert_eq!(output, vec![ResolveMessage::output("x = -2, 3")]);
+        assert_eq!(resolver.namespace.get("x"), Some(&NamespaceElement::Number(3.)));
+    }
+
+    #[test]
+    fn full_numeric_equations() {
+        let x = [
+            "let f(t) = t * exp(t)",
+            "f(x) = 1",
+            "ln(y) = 1",
+            "exp(z) = -1",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("f(t) = [...]")),
+            (1, ResolveMessage::output("x ≈ 0.5671432904097838")),
+            (2, ResolveMessage::output("y ≈ 2.718281828459045")),
+            (3, ResolveMessage::error("Could not find a solution, the numeric method did not converge")),
+        ]);
+        assert_eq!(resolver.namespace.get("y"), Some(&NamespaceElement::Number(std::f64::consts::E)));
+    }
+
+    #[test]
+    fn full_linear_system() {
+        let x = [
+            "x + y = 10",
+            "x - y = 2",
+            "x * y",
+            "a + b + c = 1",
+            "a = 2",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("x = 6, y = 4")),
+            (1, ResolveMessage::output("x = 6, y = 4")),
+            (2, ResolveMessage::output("? = 24")),
+            // Not enough equations, the lines are resolved one by one
+            (3, ResolveMessage::error("Could not resolve an equation with more than one unknown")),
+            (4, ResolveMessage::output("a = 2")),
+        ]);
+    }
+
+    #[test]
+    fn full_linear_system_without_solution() {
+        let x = [
+            "x + y = 1",
+            "2x + 2y = 3",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::error("System of equations has no solution")),
+            (1, ResolveMessage::error("System of equations has no solution")),
+        ]);
+    }
+
+    #[test]
+    fn full_document_edits() {
+        let lines = |x: &[&str]| x.iter().map(|a| a.to_string()).collect::<Vec<String>>();
+        let mut doc = Document::new(lines(&["r = 2 [m]", "let area(r) = pi r^2", "A = area(r)", "A to [cm^2]", "n = 3"]));
+
+        let updates = doc.set_line(0, "r = 3 [m]".into());
+        // n does not depend on r
+        assert_eq!(updates.keys().copied().collect::<Vec<usize>>(), vec![0, 1, 2, 3]);
+        assert_eq!(updates[&3], vec![ResolveMessage::output("? = 282743.33882308134 cm^2")]);
+
+        doc.insert_line(1, "r + 1 [m]".into());
+        doc.remove_line(4);
+        doc.set_line(1, "k = 2".into());
+
+        // Editing gives the same result as resolving the edited text from scratch
+        let text: Vec<String> = (0..doc.len()).map(|i| doc.line(i).to_string()).collect();
+        assert_eq!(doc.all_messages(), Document::new(text).all_messages());
+    }
+
+    #[test]
+    fn full_rational_mode() {
+        let x = [
+            "1/3 + 1/6",
+            "0.1 + 0.2",
+            "2^100",
+            "x = 1/3",
+            "x * 3",
+            "sqrt(2)",
+            "1/3 + 0.1 = 13/30",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new().with_number_mode(NumberMode::Rational(RationalFormat::Fraction));
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("? = 1/2")),
+            (1, ResolveMessage::output("? = 3/10")),
+            (2, ResolveMessage::output("? = 1267650600228229401496703205376")),
+            (3, ResolveMessage::output("x = 1/3")),
+            (4, ResolveMessage::output("? = 1")),
+            // Irrational operations fall back to floats
+            (5, ResolveMessage::output("? = 1.4142135623730951")),
+            (6, ResolveMessage::output("true")),
+        ]);
+    }
+
+    #[test]
+    fn full_rational_mode_solutions() {
+        let x = [
+            "3 z = 1",
+            "z * 3",
+            "y^2 = 1/4",
+            "a + b = 1",
+            "a - b = 1/3",
+            "w^2 = 2",
+            "(1/3)^100000",
+            "(1001/1000)^10000 > 1",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new().with_number_mode(NumberMode::Rational(RationalFormat::Fraction));
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("z = 1/3")),
+            (1, ResolveMessage::output("? = 1")),
+            (2, ResolveMessage::output("y = -1/2, 1/2")),
+            (2, ResolveMessage::info("y has 2 solutions, so it was not assigned")),
+            (3, ResolveMessage::output("a = 2/3, b = 1/3")),
+            (4, ResolveMessage::output("a = 2/3, b = 1/3")),
+            // Irrational solutions stay floats
+            (5, ResolveMessage::output("w = -1.4142135623730951, 1.414213562373095")),
+            (5, ResolveMessage::info("w has 2 solutions, so it was not assigned")),
+            (6, ResolveMessage::error("The power is too small to be calculated, it has more than 10000 exact digits")),
+            (7, ResolveMessage::output("true")),
+        ]);
+    }
+
+    #[test]
+    fn full_rational_mode_decimals() {
+        let x = [
+            "1/8 + 1/4",
+            "2/3",
+            "19.99 * 3",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new().with_number_mode(NumberMode::Rational(RationalFormat::Decimal));
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("? = 0.375")),
+            (1, ResolveMessage::output("? = 0.6666666666666666")),
+            (2, ResolveMessage::output("? = 59.97")),
+        ]);
+    }
+
+    #[test]
+    fn full_decimal_mode() {
+        let x = [
+            "price = 19.99",
+            "price * 3",
+            "0.1 + 0.2 = 0.3",
+            "12345678901234567.89 + 0.01",
+            "1 / 3",
+            "sqrt(2)",
+            "0.1 [m] + 0.2 [m]",
+        ];
+        let mut resolver = Resolver::new().with_number_mode(NumberMode::Decimal { precision: DEFAULT_DECIMAL_PRECISION });
+
+        let output = resolve_lines_with(&mut resolver, x.iter().map(|a| a.to_string()).collect());
+        let output: Vec<(usize, String)> = output.into_iter().map(|(line, message)| (line, message.content)).collect();
+
+        assert_eq!(output, vec![
+            (1, "price = 19.99".into()),
+            (2, "? = 59.97".into()),
+            (3, "true".into()),
+            (4, "? = 12345678901234567.9".into()),
+            (5, "? = 0.3333333333333333333333333333".into()),
+            // Irrational operations fall back to floats
+            (6, "? = 1.4142135623730951".into()),
+            (7, "Units cannot be used in decimal mode, quantities are calculated with floats".into()),
+        ]);
+    }
+
+    #[test]
+    fn full_decimal_mode_precision() {
+        let mut resolver = Resolver::new().with_number_mode(NumberMode::Decimal { precision: 5 });
+
+        let output = resolve_lines_with(&mut resolver, vec!["2 / 3".into(), "100.25 * 4".into()]);
+
+        assert_eq!(output, vec![
+            (1, ResolveMessage::output("? = 0.66667")),
+            (2, ResolveMessage::output("? = 401")),
+        ]);
+    }
+
+    #[test]
+    fn full_complex_numbers() {
+        let x = [
+            "z = 3 + 4i",
+            "abs(z)",
+            "conj(z) / (1 - i)",
+            "i^2",
+            "(-8)^(1/3)",
+            "exp(i * pi) + ln(-1)",
+            "acos(2)",
+            "sin(i)",
+            "2i [m]",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("z = 3 + 4i")),
+            (1, ResolveMessage::output("? = 5")),
+            (2, ResolveMessage::output("? = 3.5 - 0.5i")),
+            (3, ResolveMessage::output("? = -1")),
+            (4, ResolveMessage::output("? = 1 + 1.732050807568877i")),
+            (5, ResolveMessage::output("? = -1 + 3.141592653589793i")),
+            (6, ResolveMessage::error("acos(2) is undefined")),
+            (7, ResolveMessage::error("sin does not accept complex numbers")),
+            (8, ResolveMessage::error("Complex numbers cannot have units")),
+        ]);
+    }
+
+    #[test]
+    fn full_matrices() {
+        let x = [
+            "A = ((1, 2), (3, 4))",
+            "v = (1, 1)",
+            "A * v + 2 * v",
+            "A^-1",
+            "det(A) * transpose(A)",
+            "dot(v, (3, 4))",
+            "cross((1, 0, 0), (0, 1, 0))",
+            "A + v",
+            "((1, 2), (3))",
+            "inv(((1, 2), (2, 4)))",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("A = ((1, 2), (3, 4))")),
+            (1, ResolveMessage::output("v = (1, 1)")),
+            (2, ResolveMessage::output("? = (5, 9)")),
+            (3, ResolveMessage::output("? = ((-2, 1), (1.5, -0.5))")),
+            (4, ResolveMessage::output("? = ((-2, -6), (-4, -8))")),
+            (5, ResolveMessage::output("? = 7")),
+            (6, ResolveMessage::output("? = (0, 0, 1)")),
+            (7, ResolveMessage::error("Cannot add matrices of size 2x2 and 2x1")),
+            (8, ResolveMessage::error("Matrix rows must have the same length")),
+            (9, ResolveMessage::error("Matrix is not invertible")),
+        ]);
+    }
+
+    #[test]
+    fn full_lists() {
+        let x = [
+            "prices = 10, 20, 30",
+            "prices * 1.23",
+            "mean(prices) + median(prices)",
+            "stdev(prices)",
+            "max(prices) - min(prices)",
+            "sum(prices) / len(prices)",
+            "prices - (1, 2)",
+            "prices + (1, 2)",
+            "d = 1 [m], 2.5 [km]",
+            "d to [km]",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("prices = 10, 20, 30")),
+            (1, ResolveMessage::output("? = 12.3, 24.6, 36.9")),
+            (2, ResolveMessage::output("? = 40")),
+            (3, ResolveMessage::output("? = 10")),
+            (4, ResolveMessage::output("? = 20")),
+            (5, ResolveMessage::output("? = 20")),
+            (6, ResolveMessage::error("Lists cannot be combined with matrices")),
+            (7, ResolveMessage::error("Lists cannot be combined with matrices")),
+            (8, ResolveMessage::output("d = 1 m, 2500 m")),
+            (9, ResolveMessage::output("? = 0.001 km, 2.5 km")),
+        ]);
+    }
+
+    #[test]
+    fn full_equation_trace() {
+        let mut resolver = Resolver::new().with_trace();
+
+        let output = resolver.resolve_line(parse(&tokenize("3x + 2 = 14").unwrap()).unwrap());
+
+        assert_eq!(output, vec![
+            ResolveMessage::output("x = 4"),
+            ResolveMessage::info("subtract 2 from both sides"),
+            ResolveMessage::info("divide both sides by 3"),
+        ]);
+        assert_eq!(
+            resolver.last_trace().unwrap().to_latex(),
+            "\\begin{align*}\n(3 x)+2 &= 14 \\\\\n3 x &= 12 && \\text{subtract 2 from both sides} \\\\\nx &= 4 && \\text{divide both sides by 3}\n\\end{align*}"
+        );
+    }
+
+    #[test]
+    fn full_equation_trace_of_inverse_operations() {
+        let x = [
+            "2 - x = 10",
+            "2 / y = 4",
+            "2 z + 3 = z + 7",
+            "3 v = v",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new().with_trace();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("x = -8")),
+            (0, ResolveMessage::info("subtract 2 from both sides")),
+            (0, ResolveMessage::info("multiply both sides by -1")),
+            (1, ResolveMessage::output("y = 0.5")),
+            (1, ResolveMessage::info("take the reciprocal of both sides")),
+            (1, ResolveMessage::info("multiply both sides by 2")),
+            (2, ResolveMessage::output("z = 4")),
+            (2, ResolveMessage::info("collect the terms on the left side")),
+            (2, ResolveMessage::info("add 4 to both sides")),
+            (3, ResolveMessage::output("v = 0")),
+            (3, ResolveMessage::info("collect the terms on the left side")),
+            (3, ResolveMessage::info("divide both sides by 2")),
+        ]);
+        resolver.resolve_line(parse(&tokenize("2 w + 3 = 9 - w").unwrap()).unwrap());
+        assert_eq!(
+            resolver.last_trace().unwrap().to_latex(),
+            "\\begin{align*}\n(2 w)+3 &= 9-w \\\\\n(3 w)-6 &= 0 && \\text{collect the terms on the left side} \\\\\n3 w &= 6 && \\text{add 6 to both sides} \\\\\nw &= 2 && \\text{divide both sides by 3}\n\\end{align*}"
+        );
+    }
+
+    #[test]
+    fn full_simplify() {
+        let x = [
+            "simplify(2x + 3x)",
+            "simplify(x * x * 2 + 0)",
+            "a = 4",
+            "simplify(a * x - x / 2 + 1 - 1)",
+            "simplify(a + 2)",
+            "simplify(x, 2)",
+            "2x + 3x",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("? = 5 x")),
+            (1, ResolveMessage::output("? = 2 x^2")),
+            (2, ResolveMessage::output("a = 4")),
+            (3, ResolveMessage::output("? = 3.5 x")),
+            (4, ResolveMessage::output("? = 6")),
+            (5, ResolveMessage::error("simplify expects 1 argument, got 2")),
+            (6, ResolveMessage::error("Could not resolve expression with unknown")),
+            (6, ResolveMessage::info("Hint: To solve for an unknown, make this into an equation")),
+        ]);
+        let simplified = resolver.resolve_line(parse(&tokenize("simplify(x^2 / x - 0 * y)").unwrap()).unwrap());
+        assert_eq!(simplified, vec![ResolveMessage::output("? = x")]);
+    }
+
+    #[test]
+    fn full_differentiation() {
+        let x = [
+            "diff(x^3 + 2x, x)",
+            "let f(x) = x^2 * sin(x)",
+            "diff(f(t), t)",
+            "let g(x) = diff(f(x), x)",
+            "g(0)",
+            "let h(x) = diff(x^3, x)",
+            "h(2)",
+            "diff(floor(x), x)",
+            "diff(x^2, 2)",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("? = 3 x^2 + 2")),
+            (1, ResolveMessage::output("f(x) = [...]")),
+            (2, ResolveMessage::output("? = 2 t * sin(t) + t^2 * cos(t)")),
+            (3, ResolveMessage::output("g(x) = [...]")),
+            (4, ResolveMessage::output("? = 0")),
+            (5, ResolveMessage::output("h(x) = [...]")),
+            (6, ResolveMessage::output("? = 12")),
+            (7, ResolveMessage::error("Cannot differentiate floor")),
+            (8, ResolveMessage::error("diff expects an unknown as its second argument")),
+        ]);
+    }
+
+    #[test]
+    fn full_integrals_and_series() {
+        let x = [
+            "integrate(x^2, x, 0, 3)",
+            "sum(k^2, k, 1, 10)",
+            "prod(k, k, 1, 5)",
+            "k = 4",
+            "sum(k, k, 1, 3)",
+            "sum(1, 2, 3, k)",
+            "let F(n) = sum(1 / k^2, k, 1, n)",
+            "F(2)",
+            "integrate(3 [m], t, 0, 2)",
+            "sum(k, k, 1, 1.5)",
+            "sum(k, k, 5, 1)",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("? = 9")),
+            (1, ResolveMessage::output("? = 385")),
+            (2, ResolveMessage::output("? = 120")),
+            (3, ResolveMessage::output("k = 4")),
+            (4, ResolveMessage::output("? = 6")),
+            (5, ResolveMessage::output("? = 10")),
+            (6, ResolveMessage::output("F(n) = [...]")),
+            (7, ResolveMessage::output("? = 1.25")),
+            (8, ResolveMessage::output("? = 6 m")),
+            (9, ResolveMessage::error("sum expects integer bounds")),
+            (10, ResolveMessage::output("? = 0")),
+        ]);
+
+        let latex = |x: &str| parse(&tokenize(x).unwrap()).unwrap().to_latex();
+        assert_eq!(latex("integrate(x^2, x, 0, 1)"), "\\int_{0}^{1} x^2 \\, dx");
+        assert_eq!(latex("sum(k + 1, k, 1, n)"), "\\sum_{k=1}^{n} (k+1)");
+        assert_eq!(latex("prod(k, k, 1, 5)"), "\\prod_{k=1}^{5} k");
+    }
+
+    #[test]
+    fn full_function_signatures() {
+        let x = [
+            "let f(x, y) = x * y + 1",
+            "f(2, 3)",
+            "f(2)",
+            "f(1, 2, 3)",
+            "f",
+            "let g(x, x) = x",
+            "let h() = 3",
+            "f(h(), 2)",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("f(x, y) = [...]")),
+            (1, ResolveMessage::output("? = 7")),
+            (2, ResolveMessage::error("f(x, y) expects 2 arguments, got 1")),
+            (3, ResolveMessage::error("f(x, y) expects 2 arguments, got 3")),
+            (4, ResolveMessage::output("f(x, y) = x * y + 1")),
+            (5, ResolveMessage::error("Repeated function argument: x")),
+            (6, ResolveMessage::output("h() = [...]")),
+            (7, ResolveMessage::output("? = 7")),
+        ]);
+        match resolver.namespace.get("f") {
+            Some(NamespaceElement::Function(function)) => {
+                assert_eq!(function.params, vec!["x".to_string(), "y".to_string()]);
+                assert_eq!(function.arity(), 2);
+                assert_eq!(function.signature("f"), "f(x, y)");
+            }
+            other => panic!("f is not a function: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn full_recursive_functions() {
+        let x = [
+            "let fact(n) = if(n, n * fact(n - 1), 1)",
+            "fact(5)",
+            "let fib(n) = if(n - 1, if(n, fib(n - 1) + fib(n - 2), 0), 1)",
+            "let twice(n) = 2 * fib(n)",
+            "twice(10)",
+            "let loop(n) = loop(n + 1)",
+            "loop(1)",
+            "if(0, 1, 2)",
+            "if(1, 2)",
+            "let f(n) = if(n = 0, 1, n f(n - 1))",
+            "f(5)",
+            "if(0.1 + 0.2 = 0.3, 1, 2)",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("fact(n) = [...]")),
+            (1, ResolveMessage::output("? = 120")),
+            (2, ResolveMessage::output("fib(n) = [...]")),
+            (3, ResolveMessage::output("twice(n) = [...]")),
+            (4, ResolveMessage::output("? = 110")),
+            (5, ResolveMessage::output("loop(n) = [...]")),
+            (6, ResolveMessage::error("Recursion limit of 200 nested calls exceeded")),
+            (7, ResolveMessage::output("? = 2")),
+            (8, ResolveMessage::error("if expects 3 arguments, got 2")),
+            (9, ResolveMessage::output("f(n) = [...]")),
+            (10, ResolveMessage::output("? = 120")),
+            (11, ResolveMessage::output("? = 1")),
+        ]);
+
+        let mut resolver = Resolver::new().with_recursion_limit(3);
+        resolver.resolve_line(parse(&tokenize("let fact(n) = if(n, n * fact(n - 1), 1)").unwrap()).unwrap());
+        assert_eq!(resolver.resolve_line(parse(&tokenize("fact(2)").unwrap()).unwrap()), vec![ResolveMessage::output("? = 2")]);
+        assert_eq!(
+            resolver.resolve_line(parse(&tokenize("fact(3)").unwrap()).unwrap()),
+            vec![ResolveMessage::error("Recursion limit of 3 nested calls exceeded")]
+        );
+        assert_eq!(Resolver::new().with_recursion_limit(100_000).recursion_limit, MAX_RECURSION_LIMIT);
+    }
+
+    #[test]
+    fn full_functions_capture_globals() {
+        let x = [
+            "let area(r) = pi r^2",
+            "area(1)",
+            "a = 3",
+            "let g(x) = a x",
+            "g(2)",
+            "g",
+            "let f(a) = a^2",
+            "f(2)",
+            "let s(n) = sum(a * k, k, 1, n)",
+            "s(3)",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("area(r) = [...]")),
+            (1, ResolveMessage::output(&format!("? = {}", std::f64::consts::PI))),
+            (2, ResolveMessage::output("a = 3")),
+            (3, ResolveMessage::output("g(x) = [...]")),
+            (4, ResolveMessage::output("? = 6")),
+            (5, ResolveMessage::output("g(x) = 3 x")),
+            (6, ResolveMessage::output("f(a) = [...]")),
+            (7, ResolveMessage::output("? = 4")),
+            (8, ResolveMessage::output("s(n) = [...]")),
+            (9, ResolveMessage::output("? = 18")),
+        ]);
+    }
+
+    #[test]
+    fn full_comparisons_and_inequalities() {
+        let x = [
+            "3 < 4",
+            "2 [km] >= 3000 [m]",
+            "1 != 1",
+            "2x + 1 < 7",
+            "5 - y >= 1",
+            "z + 1 > z",
+            "x^2 < 4",
+            "3 [m] > 2 [s]",
+            "let sgn(t) = if(t > 0, 1, if(t < 0, -1, 0))",
+            "sgn(-5)",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("true")),
+            (1, ResolveMessage::output("false")),
+            (2, ResolveMessage::output("false")),
+            (3, ResolveMessage::output("x < 3")),
+            (4, ResolveMessage::output("y <= 4")),
+            (5, ResolveMessage::info("Inequality is true for every z")),
+            (6, ResolveMessage::error("Only linear inequalities can be solved")),
+            (7, ResolveMessage::error("Cannot compare [m] with [s]")),
+            (8, ResolveMessage::output("sgn(t) = [...]")),
+            (9, ResolveMessage::output("? = -1")),
+        ]);
+    }
+
+    #[test]
+    fn full_equality_within_tolerance() {
+        let x = [
+            "0.1 + 0.2 = 0.3",
+            "2 + 2 = 4",
+            "1 / 3 = 0.3334",
+            "(0.1 + 0.2) * i = 0.3 i",
+            "1 [m] = 1 [s]",
+            "0.1 + 0.2 != 0.3",
+            "0.1 + 0.2 <= 0.3",
+            "0.3 >= 0.1 + 0.2",
+            "0.1 + 0.2 > 0.3",
+            "(0.1 + 0.2) * i != 0.3 i",
+            "1 / 3 != 0.3334",
+        ];
+        let lines = || x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate()
+            .collect();
+
+        let output = Resolver::new().resolve(lines());
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("true (≈)")),
+            (1, ResolveMessage::output("true")),
+            (2, ResolveMessage::output("false")),
+            (3, ResolveMessage::output("true (≈)")),
+            (4, ResolveMessage::output("false")),
+            (5, ResolveMessage::output("false")),
+            (6, ResolveMessage::output("true")),
+            (7, ResolveMessage::output("true")),
+            (8, ResolveMessage::output("false")),
+            (9, ResolveMessage::output("false")),
+            (10, ResolveMessage::output("true")),
+        ]);
+
+        let output = Resolver::new().with_tolerance(Tolerance::new(1e-3, 0.)).resolve(lines());
+        assert_eq!(output[2], (2, ResolveMessage::output("true (≈)")));
+        assert_eq!(output[10], (10, ResolveMessage::output("false")));
+    }
+
+    #[test]
+    fn full_boolean_logic() {
+        let x = [
+            "true and false",
+            "not false or false",
+            "p = 3 < 4",
+            "q = not p",
+            "p xor q",
+            "1 and true",
+            "let inside(t) = if(p and t > 0 and t < 1, 1, 0)",
+            "inside(0.5)",
+            "inside(2)",
+            "not not true",
+            "not not not p",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("false")),
+            (1, ResolveMessage::output("true")),
+            (2, ResolveMessage::output("p = true")),
+            (3, ResolveMessage::output("q = false")),
+            (4, ResolveMessage::output("true")),
+            (5, ResolveMessage::error("and expects true or false")),
+            (6, ResolveMessage::output("inside(t) = [...]")),
+            (7, ResolveMessage::output("? = 1")),
+            (8, ResolveMessage::output("? = 0")),
+            (9, ResolveMessage::output("true")),
+            (10, ResolveMessage::output("false")),
+        ]);
+        assert_eq!(resolver.namespace.get("p"), Some(&NamespaceElement::Boolean(true)));
+
+        let latex = |x: &str| parse(&tokenize(x).unwrap()).unwrap().to_latex();
+        assert_eq!(latex("not (a or b) and c"), "\\neg (a \\lor b) \\land c");
+        assert_eq!(latex("a xor true"), "a \\oplus \\mathrm{true}");
+        assert_eq!(latex("not not a"), "\\neg \\neg a");
+    }
+
+    #[test]
+    fn full_units_on_implied_products() {
+        let x = [
+            "x = 8",
+            "abs(2 x [m])",
+            "sqrt(2 x [m^2])",
+            "simplify(2 x [m])",
+            "y [m] + 2 y [m] = 6 [m]",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("x = 8")),
+            (1, ResolveMessage::output("? = 16 m")),
+            (2, ResolveMessage::output("? = 4 m")),
+            (3, ResolveMessage::output("? = 16 m")),
+            (4, ResolveMessage::output("y = 2")),
+        ]);
+    }
+
+    #[test]
+    fn full_arguments_without_separator() {
+        let mut resolver = Resolver::new();
+        resolver.resolve_line(parse(&tokenize("x = 2").unwrap()).unwrap());
+
+        assert_eq!(
+            resolver.resolve_line(parse(&tokenize("sin(x^2 sin(x))").unwrap()).unwrap()),
+            vec![ResolveMessage::error("The arguments of sin must be separated by commas or operations")]
+        );
+        assert_eq!(
+            resolver.resolve_line(parse(&tokenize("sqrt(x^2 x)").unwrap()).unwrap()),
+            vec![ResolveMessage::error("The arguments of sqrt must be separated by commas or operations")]
+        );
+    }
+
+    #[test]
+    fn full_integrals_with_singular_endpoints() {
+        let x = [
+            "integrate(1 / sqrt(x), x, 0, 1)",
+            "integrate(1 / x, x, 0, 1)",
+            "integrate(sin(1000 x), x, 0, 100)",
+            "integrate(sin(1 / x), x, 1e-5, 1)",
+            "integrate(1 / (x - 1), x, 0, 2)",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("? = 2")),
+            (1, ResolveMessage::error("The integral did not converge")),
+            (2, ResolveMessage::error("The integral did not converge")),
+            (3, ResolveMessage::error("The integral did not converge")),
+            (4, ResolveMessage::error("The integral is undefined, the integrand is not finite on the interval")),
+        ]);
+    }
+
+    #[test]
+    fn full_list_values_passed_to_one_parameter() {
+        let x = [
+            "xs = 1, 2, 3",
+            "let p(v) = mean(v)",
+            "p(xs)",
+            "p(1, 2)",
+            "let q(v) = p(v) + 1",
+            "q(xs)",
+            "let r(n) = r(n, 1)",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("xs = 1, 2, 3")),
+            (1, ResolveMessage::output("p(v) = [...]")),
+            (2, ResolveMessage::output("? = 2")),
+            (3, ResolveMessage::error("p(v) expects 1 argument, got 2")),
+            (4, ResolveMessage::output("q(v) = [...]")),
+            (5, ResolveMessage::output("? = 3")),
+            (6, ResolveMessage::error("r(n) expects 1 argument, got 2")),
+        ]);
+    }
+
+    #[test]
+    fn full_scientific_notation_reads_back() {
+        let x = [
+            "5 * 10^-7",
+            "5e-7 * 2",
+            "2.5E+2 [m] to [km]",
+            "1e-3 - 1 / 1000",
+        ];
+        let x = x.iter()
+            .map(|a| tokenize(a).unwrap())
+            .map(|a| parse(&a).unwrap())
+            .enumerate();
+        let mut resolver = Resolver::new();
+
+        let output = resolver.resolve(x.collect());
+
+        assert_eq!(output, vec![
+            (0, ResolveMessage::output("? = 5e-7")),
+            (1, ResolveMessage::output("? = 0.000001")),
+            (2, ResolveMessage::output("? = 0.25 km")),
+            (3, ResolveMessage::output("? = 0")),
+        ]);
+    }
+}